@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector2;
+
+use image::{RgbImage, Rgb};
+
+use crate::{
+    draw_field,
+    contrast_ratio,
+    FlagSpec,
+    FlagBackground,
+    FlagForeground
+};
+
+
+/// parts covering less of a layer than this are left out, mostly antialiased edges
+const MIN_OVERLAP: f32 = 0.02;
+
+/// a color under a layer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Overlap
+{
+    pub color: Rgb<u8>,
+    /// fraction of the layer's area over this color
+    pub fraction: f32,
+    /// of the layer's edge or its outline against this color, whichever stands out more
+    pub contrast: f32
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerContrast
+{
+    /// the layer is the canton's emblem instead of a layer of the field
+    pub canton: bool,
+    pub index: usize,
+    /// color of the layer's edge, its fimbriation if it has one
+    pub color: Rgb<u8>,
+    pub outline: Option<Rgb<u8>>,
+    /// sorted by fraction, biggest first
+    pub overlaps: Vec<Overlap>
+}
+
+impl LayerContrast
+{
+    /// lowest contrast against anything under the layer, `None` if the layer isnt visible
+    pub fn min_contrast(&self) -> Option<f32>
+    {
+        self.overlaps.iter().map(|x| x.contrast).min_by(f32::total_cmp)
+    }
+}
+
+/// 2 parts of a background that touch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NeighbourContrast
+{
+    pub canton: bool,
+    /// indices of the parts, `a` comes before `b`
+    pub a: usize,
+    pub b: usize,
+    pub contrast: f32
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastReport
+{
+    pub layers: Vec<LayerContrast>,
+    pub neighbours: Vec<NeighbourContrast>
+}
+
+impl ContrastReport
+{
+    pub fn min_layer_contrast(&self) -> Option<f32>
+    {
+        self.layers.iter().filter_map(LayerContrast::min_contrast).min_by(f32::total_cmp)
+    }
+
+    pub fn min_neighbour_contrast(&self) -> Option<f32>
+    {
+        self.neighbours.iter().map(|x| x.contrast).min_by(f32::total_cmp)
+    }
+
+    /// every layer and every pair of touching parts have at least these contrast ratios
+    pub fn passes(&self, min_layer: f32, min_neighbour: f32) -> bool
+    {
+        self.min_layer_contrast().is_none_or(|x| x >= min_layer)
+            && self.min_neighbour_contrast().is_none_or(|x| x >= min_neighbour)
+    }
+}
+
+fn neighbour_contrasts(background: &FlagBackground, canton: bool) -> Vec<NeighbourContrast>
+{
+    (0..background.division.parts(background.lines.len())).flat_map(|b|
+    {
+        background.division.neighbours(b).into_iter().map(move |a|
+        {
+            NeighbourContrast{canton, a, b, contrast: contrast_ratio(background.color(a), background.color(b))}
+        })
+    }).collect()
+}
+
+/// `skip` leaves out pixels hidden by something drawn later
+fn layer_contrasts(
+    background: &FlagBackground,
+    layers: &[FlagForeground],
+    canton: bool,
+    width: u32,
+    height: u32,
+    skip: impl Fn(u32, u32) -> bool
+) -> Vec<LayerContrast>
+{
+    let size = Vector2::new(width as f32, height as f32);
+
+    layers.iter().enumerate().map(|(index, layer)|
+    {
+        let under = draw_field(background, &layers[..index], width, height);
+
+        let color = layer.edge_color();
+        let outline = layer.outline.map(|x| x.color);
+
+        let mut counts: HashMap<Rgb<u8>, u32> = HashMap::new();
+        under.enumerate_pixels().filter(|(x, y, _)| !skip(*x, *y)).for_each(|(x, y, pixel)|
+        {
+            let pos = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+
+            if layer.distance(pos, size) < 0.0
+            {
+                *counts.entry(*pixel).or_default() += 1;
+            }
+        });
+
+        let total: u32 = counts.values().sum();
+
+        let mut overlaps: Vec<_> = counts.into_iter().map(|(under, count)|
+        {
+            let contrast = outline.into_iter().fold(contrast_ratio(color, under), |contrast, outline|
+            {
+                contrast.max(contrast_ratio(outline, under))
+            });
+
+            Overlap{color: under, fraction: count as f32 / total as f32, contrast}
+        }).filter(|x| x.fraction >= MIN_OVERLAP).collect();
+
+        overlaps.sort_by(|a, b| b.fraction.total_cmp(&a.fraction));
+
+        LayerContrast{canton, index, color, outline, overlaps}
+    }).collect()
+}
+
+/// contrast of every layer against what it covers and of every pair of touching background parts,
+/// measured on a `width` by `height` render
+pub fn spec_contrast(spec: &FlagSpec, width: u32, height: u32) -> ContrastReport
+{
+    let canton_size = spec.canton.as_ref().map(|canton| canton.pixel_size(width, height));
+
+    let hidden = |x, y| canton_size.is_some_and(|(canton_width, canton_height)| x < canton_width && y < canton_height);
+
+    let mut layers = layer_contrasts(&spec.background, &spec.layers, false, width, height, hidden);
+    let mut neighbours = neighbour_contrasts(&spec.background, false);
+
+    if let (Some(canton), Some((canton_width, canton_height))) = (&spec.canton, canton_size)
+    {
+        let emblem = canton.emblem.as_slice();
+        layers.extend(layer_contrasts(&canton.background, emblem, true, canton_width, canton_height, |_, _| false));
+
+        neighbours.extend(neighbour_contrasts(&canton.background, true));
+    }
+
+    ContrastReport{layers, neighbours}
+}
+
+/// 2 colors that touch in an image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Boundary
+{
+    pub a: Rgb<u8>,
+    pub b: Rgb<u8>,
+    /// amount of touching pixel pairs
+    pub length: u32,
+    pub contrast: f32
+}
+
+/// colors that touch anywhere in a rendered flag, lowest contrast first
+///
+/// colors taking up less than `MIN_OVERLAP` of the image are treated as antialiasing and skipped over
+pub fn image_contrast(image: &RgbImage) -> Vec<Boundary>
+{
+    let mut areas: HashMap<Rgb<u8>, u32> = HashMap::new();
+    image.pixels().for_each(|pixel| *areas.entry(*pixel).or_default() += 1);
+
+    let min_area = (image.width() * image.height()) as f32 * MIN_OVERLAP;
+    let solid = |pixel: &Rgb<u8>| areas[pixel] as f32 >= min_area;
+
+    let mut lengths: HashMap<(Rgb<u8>, Rgb<u8>), u32> = HashMap::new();
+
+    let mut touch = |a: &Rgb<u8>, b: &Rgb<u8>|
+    {
+        if a != b && solid(a) && solid(b)
+        {
+            let key = if a.0 < b.0 { (*a, *b) } else { (*b, *a) };
+
+            *lengths.entry(key).or_default() += 1;
+        }
+    };
+
+    // looks 2 pixels ahead to step over a single antialiased pixel
+    image.enumerate_pixels().for_each(|(x, y, pixel)|
+    {
+        [(1, 0), (2, 0), (0, 1), (0, 2)].into_iter().for_each(|(dx, dy)|
+        {
+            if x + dx < image.width() && y + dy < image.height()
+            {
+                touch(pixel, image.get_pixel(x + dx, y + dy));
+            }
+        });
+    });
+
+    let mut boundaries: Vec<_> = lengths.into_iter().map(|((a, b), length)|
+    {
+        Boundary{a, b, length, contrast: contrast_ratio(a, b)}
+    }).collect();
+
+    boundaries.sort_by(|a, b| a.contrast.total_cmp(&b.contrast));
+
+    boundaries
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{Canton, Division, FlagForegroundShape};
+
+
+    const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+    const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+    const RED: Rgb<u8> = Rgb([200, 16, 46]);
+
+    fn pairs(division: Division, lines: Vec<Rgb<u8>>) -> Vec<(usize, usize)>
+    {
+        let spec = FlagSpec::builder(FlagBackground::divided(division, lines)).build();
+
+        spec_contrast(&spec, 120, 80).neighbours.into_iter().map(|x| (x.a, x.b)).collect()
+    }
+
+    #[test]
+    fn layer_over_stripes()
+    {
+        let hidden = FlagForeground::new(RED, FlagForegroundShape::Circle)
+            .with_position(Vector2::new(-0.4, -0.4))
+            .with_scale(0.1);
+
+        let spec = FlagSpec::builder(FlagBackground::new(true, vec![WHITE, BLACK]))
+            .layer(FlagForeground::new(WHITE, FlagForegroundShape::Circle))
+            .layer(hidden)
+            .canton(Canton::new(0.2, 0.2, FlagBackground::solid(RED)))
+            .build();
+
+        let report = spec_contrast(&spec, 120, 80);
+
+        let circle = &report.layers[0];
+        assert_eq!(circle.overlaps.len(), 2);
+
+        let overlap = |color| *circle.overlaps.iter().find(|x| x.color == color).unwrap();
+
+        assert!((overlap(WHITE).fraction - 0.5).abs() < 0.02);
+        assert!((overlap(BLACK).fraction - 0.5).abs() < 0.02);
+        assert_eq!(overlap(WHITE).contrast, 1.0);
+        assert!((overlap(BLACK).contrast - 21.0).abs() < 0.01);
+        assert_eq!(circle.min_contrast(), Some(1.0));
+
+        // the second layer is all under the canton
+        assert_eq!(report.layers[1].min_contrast(), None);
+        assert_eq!(report.min_layer_contrast(), Some(1.0));
+
+        assert_eq!(report.neighbours.len(), 1);
+        assert!(!report.passes(1.25, 1.0));
+        assert!(report.passes(1.0, 21.0 - 0.01));
+    }
+
+    #[test]
+    fn neighbour_pairs()
+    {
+        assert_eq!(pairs(Division::Quartered, vec![WHITE, BLACK, RED, BLACK]), [(0, 1), (1, 2), (0, 3), (2, 3)]);
+        assert_eq!(pairs(Division::Chevron{depth: 0.4}, vec![WHITE, BLACK, RED]), [(0, 1), (0, 2), (1, 2)]);
+    }
+}
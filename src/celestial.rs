@@ -0,0 +1,331 @@
+use std::{
+    ops::RangeInclusive,
+    f32::consts::{PI, TAU}
+};
+
+use nalgebra::Vector2;
+
+use fastrand::Rng;
+
+use crate::{
+    polygon_distance,
+    json::Value,
+    spec::{self, SpecValue, SpecError},
+    Star
+};
+
+
+fn rotate(pos: Vector2<f32>, angle: f32) -> Vector2<f32>
+{
+    let (sin, cos) = angle.sin_cos();
+
+    Vector2::new(pos.x * cos - pos.y * sin, pos.x * sin + pos.y * cos)
+}
+
+/// a circle with an offset circle cut out of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crescent
+{
+    /// offset of the cut out circle as a fraction of the radius
+    pub phase: f32,
+    /// in radians, direction the crescent opens towards, 0 is towards the fly
+    pub rotation: f32,
+    /// offset of the center as a fraction of the flag's size
+    pub position: Vector2<f32>,
+    /// radius as a fraction of the flag's shorter side
+    pub size: f32
+}
+
+impl Crescent
+{
+    /// radius of the cut out circle as a fraction of the outer radius
+    pub const INNER_RATIO: f32 = 0.8;
+
+    /// phases where the 2 circles cross, outside of them theres either nothing left or a full ring
+    pub const PHASES: RangeInclusive<f32> = (1.0 - Self::INNER_RATIO + 0.01)..=(1.0 + Self::INNER_RATIO - 0.01);
+
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        let rotation = match rng.usize(0..4)
+        {
+            0 => -PI / 2.0,
+            1 => (rng.f32() - 0.5) * 1.2,
+            _ => 0.0
+        };
+
+        Self{
+            phase: rng.f32() * 0.25 + 0.25,
+            rotation,
+            position: Vector2::zeros(),
+            size: rng.f32() * 0.1 + 0.25
+        }
+    }
+
+    pub fn center(&self, size: Vector2<f32>) -> Vector2<f32>
+    {
+        size / 2.0 + self.position.component_mul(&size)
+    }
+
+    pub fn direction(&self) -> Vector2<f32>
+    {
+        Vector2::new(self.rotation.cos(), self.rotation.sin())
+    }
+
+    /// center of the cut out circle and its radius in pixels
+    pub fn inner(&self, center: Vector2<f32>, radius: f32) -> (Vector2<f32>, f32)
+    {
+        (center + self.direction() * (self.phase * radius), radius * Self::INNER_RATIO)
+    }
+
+    pub fn distance_at(&self, pos: Vector2<f32>, center: Vector2<f32>, radius: f32) -> f32
+    {
+        let (inner_center, inner_radius) = self.inner(center, radius);
+
+        let outer = (pos - center).magnitude() - radius;
+        let inner = (pos - inner_center).magnitude() - inner_radius;
+
+        outer.max(-inner)
+    }
+
+    /// svg path data going along the outer arc and back along the inner arc
+    pub fn path(&self, center: Vector2<f32>, radius: f32) -> String
+    {
+        let (_, inner_radius) = self.inner(center, radius);
+        let distance = self.phase * radius;
+
+        // where the 2 circles cross, measured along the direction
+        let along = (distance.powi(2) + radius.powi(2) - inner_radius.powi(2)) / (2.0 * distance);
+        let across = (radius.powi(2) - along.powi(2)).max(0.0).sqrt();
+
+        let direction = self.direction();
+        let side = Vector2::new(-direction.y, direction.x);
+
+        let start = center + direction * along + side * across;
+        let end = center + direction * along - side * across;
+
+        format!(
+            "M {} {} A {radius} {radius} 0 {} 1 {} {} A {inner_radius} {inner_radius} 0 {} 0 {} {} Z",
+            start.x, start.y,
+            u8::from(along > 0.0),
+            end.x, end.y,
+            u8::from(along > distance),
+            start.x, start.y
+        )
+    }
+
+    /// a star sitting in the opening of the crescent
+    pub fn star(&self, points: u32, star_size: f32) -> Star
+    {
+        Star{
+            points,
+            ratio: 0.382,
+            rotation: self.rotation + PI / 2.0,
+            position: Vector2::zeros(),
+            size: star_size * self.size
+        }
+    }
+
+    /// center of the star in pixels, `star_size` is a fraction of the crescent's radius
+    pub fn star_center(&self, center: Vector2<f32>, radius: f32, star_size: f32) -> Vector2<f32>
+    {
+        let (inner_center, inner_radius) = self.inner(center, radius);
+
+        inner_center + self.direction() * (inner_radius - star_size * radius * 1.2).max(0.0)
+    }
+
+    pub fn description(&self) -> String
+    {
+        "crescent".to_owned()
+    }
+
+    pub fn parameters(&self) -> Vec<(&'static str, Value)>
+    {
+        vec![
+            ("phase", self.phase.into()),
+            ("rotation", self.rotation.into()),
+            ("position", self.position.to_value()),
+            ("size", self.size.into())
+        ]
+    }
+
+    pub fn from_parameters(value: &Value) -> Result<Self, SpecError>
+    {
+        Ok(Self{
+            phase: spec::f32_in(value, "phase", Self::PHASES)?,
+            rotation: spec::f32_field(value, "rotation")?,
+            position: Vector2::from_value(spec::field(value, "position")?)?,
+            size: spec::f32_in(value, "size", 0.01..=2.0)?
+        })
+    }
+}
+
+/// a disc surrounded by rays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sun
+{
+    pub rays: u32,
+    pub wavy: bool,
+    /// radius of the disc as a fraction of the radius including the rays
+    pub ratio: f32,
+    /// offset of the center as a fraction of the flag's size
+    pub position: Vector2<f32>,
+    /// radius including the rays as a fraction of the flag's shorter side
+    pub size: f32
+}
+
+impl Sun
+{
+    const WAVES: f32 = 1.5;
+
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        Self{
+            rays: rng.u32(8..=32),
+            wavy: rng.usize(0..3) == 0,
+            ratio: rng.f32() * 0.15 + 0.45,
+            position: Vector2::zeros(),
+            size: rng.f32() * 0.1 + 0.25
+        }
+    }
+
+    pub fn center(&self, size: Vector2<f32>) -> Vector2<f32>
+    {
+        size / 2.0 + self.position.component_mul(&size)
+    }
+
+    fn sector(&self) -> f32
+    {
+        TAU / self.rays as f32
+    }
+
+    /// half of the angle a ray takes up at its base
+    fn ray_width(&self) -> f32
+    {
+        self.sector() * if self.wavy { 0.2 } else { 0.3 }
+    }
+
+    fn ray_start(&self, radius: f32) -> f32
+    {
+        radius * self.ratio * 0.9
+    }
+
+    /// angle the center of a wavy ray is offset by at `along` pixels from the sun's center
+    fn wave(&self, along: f32, radius: f32) -> f32
+    {
+        let start = self.ray_start(radius);
+        let progress = ((along - start) / (radius - start)).clamp(0.0, 1.0);
+
+        (progress * TAU * Self::WAVES).sin() * self.ray_width() * (1.0 - progress * 0.5)
+    }
+
+    fn straight_ray(&self, radius: f32) -> [Vector2<f32>; 3]
+    {
+        let start = self.ray_start(radius);
+        let width = self.ray_width();
+
+        [
+            Vector2::new(width.cos(), -width.sin()) * start,
+            Vector2::new(radius, 0.0),
+            Vector2::new(width.cos(), width.sin()) * start
+        ]
+    }
+
+    pub fn distance_at(&self, pos: Vector2<f32>, center: Vector2<f32>, radius: f32) -> f32
+    {
+        let offset = pos - center;
+        let length = offset.magnitude();
+
+        let disc = length - radius * self.ratio;
+
+        // rotate the position into the sector of the closest ray
+        let sector = self.sector();
+        let angle = offset.y.atan2(offset.x) + PI / 2.0;
+        let angle = (angle + sector / 2.0).rem_euclid(sector) - sector / 2.0;
+
+        let ray = if self.wavy
+        {
+            let start = self.ray_start(radius);
+            let progress = ((length - start) / (radius - start)).clamp(0.0, 1.0);
+
+            let half_width = self.ray_width() * (1.0 - progress);
+
+            let across = ((angle - self.wave(length, radius)).abs() - half_width) * length;
+            let along = (start - length).max(length - radius);
+
+            across.max(along)
+        } else
+        {
+            let local = Vector2::new(angle.cos(), angle.sin()) * length;
+
+            polygon_distance(local, &self.straight_ray(radius))
+        };
+
+        disc.min(ray)
+    }
+
+    /// polygons of every ray in pixels
+    pub fn rays(&self, center: Vector2<f32>, radius: f32) -> Vec<Vec<Vector2<f32>>>
+    {
+        let sector = self.sector();
+
+        (0..self.rays).map(|index|
+        {
+            let rotation = sector * index as f32 - PI / 2.0;
+
+            let points = if self.wavy
+            {
+                let start = self.ray_start(radius);
+                let samples = 16;
+
+                let side = |sign: f32|
+                {
+                    (0..=samples).map(move |sample|
+                    {
+                        let progress = sample as f32 / samples as f32;
+                        let along = start + (radius - start) * progress;
+
+                        let angle = self.wave(along, radius) + sign * self.ray_width() * (1.0 - progress);
+
+                        Vector2::new(angle.cos(), angle.sin()) * along
+                    })
+                };
+
+                side(-1.0).chain(side(1.0).collect::<Vec<_>>().into_iter().rev()).collect()
+            } else
+            {
+                self.straight_ray(radius).to_vec()
+            };
+
+            points.into_iter().map(|point| center + rotate(point, rotation)).collect()
+        }).collect()
+    }
+
+    pub fn description(&self) -> String
+    {
+        let kind = if self.wavy { "wavy" } else { "straight" };
+
+        format!("sun with {} {kind} rays", self.rays)
+    }
+
+    pub fn parameters(&self) -> Vec<(&'static str, Value)>
+    {
+        vec![
+            ("rays", self.rays.into()),
+            ("wavy", self.wavy.into()),
+            ("ratio", self.ratio.into()),
+            ("position", self.position.to_value()),
+            ("size", self.size.into())
+        ]
+    }
+
+    pub fn from_parameters(value: &Value) -> Result<Self, SpecError>
+    {
+        Ok(Self{
+            rays: spec::u32_in(value, "rays", 1..=128)?,
+            wavy: spec::bool_field(value, "wavy")?,
+            ratio: spec::f32_in(value, "ratio", 0.05..=1.0)?,
+            position: Vector2::from_value(spec::field(value, "position")?)?,
+            size: spec::f32_in(value, "size", 0.01..=2.0)?
+        })
+    }
+}
@@ -0,0 +1,642 @@
+use std::{
+    fs,
+    io,
+    fmt::{self, Display},
+    path::{Path, PathBuf}
+};
+
+use fastrand::Rng;
+
+use flaggen::{
+    RandomConfig,
+    Palette,
+    Harmony,
+    vision::Deficiency,
+    json::Value,
+    spec::{SpecValue, SpecError, FlagSpec, SPEC_VERSION, field, invalid, write_value}
+};
+
+
+pub const USAGE: &str = "usage:
+    flaggen [VIEWER OPTIONS] [SPEC]
+        opens the viewer, showing SPEC if given
+    flaggen generate [OPTIONS]
+        writes flags without opening a window
+
+viewer options:
+    --aspect H:W       height to width ratio of the flag, like 1:2 or 10:19 (default 2:3)
+    --resolution N     height of the saved flag in pixels (default 400)
+    --history N        amount of flags to remember for going back (default 100)
+    --out-dir DIR      directory saved flags and favorites.json go into (default saved)
+    --browse FILE      start with every flag of a favorites or manifest file in the history
+
+viewer keys:
+    space              next flag
+    b                  new background, keeping the rest
+    n                  new shapes in the same places and colors
+    k                  new colors, keeping the layout
+    p                  new shape parameters like ring width or cross thickness
+    e                  toggle edit mode, where up and down pick a parameter, left, right
+                       and the mouse wheel change it and clicking recolors a stripe
+    left, right        go back and forward through previous flags
+    s                  save the flag and its spec into the output directory
+    f                  add the flag to favorites.json in the output directory
+    v                  cycle through color blindness simulations
+
+generate options:
+    --count N          amount of flags to generate (default 1)
+    --size WxH         size of each flag in pixels (default 640x360)
+    --out-dir DIR      directory to write the flags into (default flags)
+    --seed SEED        seed for the whole batch (default random)
+
+options for both:
+    --format FORMAT    format of written flags, png or svg (default png)
+    --stripes W:W:..   only make striped flags with these relative stripe widths
+    --palette NAME     pick colors from a palette: heraldic, modern or muted
+    --tincture         keep metals off metals and colours off colours, needs a palette
+    --harmony NAME     colors from a scheme: complementary, triadic, analogous,
+                       split_complementary or any, instead of a palette
+    --simulate         also write how each flag looks with each kind of color blindness";
+
+#[derive(Debug)]
+pub enum CliError
+{
+    Usage(String),
+    Io(io::Error),
+    Image(image::ImageError),
+    Spec(SpecError)
+}
+
+impl Display for CliError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::Usage(x) => write!(f, "{x}\n\n{USAGE}"),
+            Self::Io(x) => write!(f, "{x}"),
+            Self::Image(x) => write!(f, "{x}"),
+            Self::Spec(x) => write!(f, "{x}")
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<io::Error> for CliError
+{
+    fn from(value: io::Error) -> Self
+    {
+        Self::Io(value)
+    }
+}
+
+impl From<image::ImageError> for CliError
+{
+    fn from(value: image::ImageError) -> Self
+    {
+        Self::Image(value)
+    }
+}
+
+impl From<SpecError> for CliError
+{
+    fn from(value: SpecError) -> Self
+    {
+        Self::Spec(value)
+    }
+}
+
+impl CliError
+{
+    pub fn exit_code(&self) -> u8
+    {
+        match self
+        {
+            Self::Usage(_) => 2,
+            _ => 1
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat
+{
+    #[default]
+    Png,
+    Svg
+}
+
+impl OutputFormat
+{
+    pub fn parse(text: &str) -> Option<Self>
+    {
+        match text
+        {
+            "png" => Some(Self::Png),
+            "svg" => Some(Self::Svg),
+            _ => None
+        }
+    }
+
+    pub fn extension(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Png => "png",
+            Self::Svg => "svg"
+        }
+    }
+
+    pub fn write(
+        &self,
+        spec: &FlagSpec,
+        path: impl AsRef<Path>,
+        width: u32,
+        height: u32
+    ) -> Result<(), CliError>
+    {
+        match self
+        {
+            Self::Png => spec.render(width, height).save(path)?,
+            Self::Svg => fs::write(path, spec.render_svg(width, height))?
+        }
+
+        Ok(())
+    }
+
+    /// writes the flag as it looks with `deficiency`
+    pub fn write_simulated(
+        &self,
+        spec: &FlagSpec,
+        deficiency: Deficiency,
+        path: impl AsRef<Path>,
+        width: u32,
+        height: u32
+    ) -> Result<(), CliError>
+    {
+        match self
+        {
+            Self::Png => deficiency.simulate_image(&spec.render(width, height)).save(path)?,
+            Self::Svg => fs::write(path, deficiency.simulate_svg(&spec.render_svg(width, height)))?
+        }
+
+        Ok(())
+    }
+}
+
+/// options the viewer and generate both take
+#[derive(Debug, Clone, Default)]
+pub struct SharedOptions
+{
+    pub format: OutputFormat,
+    pub config: RandomConfig,
+    /// writes color blindness simulations next to each flag
+    pub simulate: bool
+}
+
+impl SharedOptions
+{
+    /// parses `arg` if it's a shared option, returns `false` if it isnt one
+    fn parse_arg(
+        &mut self,
+        arg: &str,
+        mut value: impl FnMut() -> Result<String, CliError>,
+        invalid: impl Fn(&str) -> CliError
+    ) -> Result<bool, CliError>
+    {
+        match arg
+        {
+            "--format" =>
+            {
+                let value = value()?;
+                self.format = OutputFormat::parse(&value).ok_or_else(|| invalid(&value))?;
+            },
+            "--stripes" =>
+            {
+                let value = value()?;
+                self.config.stripes = Some(parse_weights(&value).ok_or_else(|| invalid(&value))?);
+            },
+            "--palette" =>
+            {
+                let value = value()?;
+                self.config.palette = Some(Palette::builtin(&value).ok_or_else(|| invalid(&value))?);
+            },
+            "--tincture" => self.config.tincture = true,
+            "--simulate" => self.simulate = true,
+            "--harmony" =>
+            {
+                let value = value()?;
+                self.config.harmonies = parse_harmonies(&value).ok_or_else(|| invalid(&value))?;
+            },
+            _ => return Ok(false)
+        }
+
+        Ok(true)
+    }
+
+    /// rejects combinations of options that dont work together, after every option is parsed
+    fn check(&self) -> Result<(), CliError>
+    {
+        if self.config.tincture && self.config.palette.is_none()
+        {
+            return Err(CliError::Usage("--tincture needs a --palette".to_owned()));
+        }
+
+        if self.config.palette.is_some() && !self.config.harmonies.is_empty()
+        {
+            return Err(CliError::Usage("--palette and --harmony cant be used together".to_owned()));
+        }
+
+        Ok(())
+    }
+}
+
+/// proportions of a flag written as height to width, like flags usually are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AspectRatio
+{
+    pub height: u32,
+    pub width: u32
+}
+
+impl Default for AspectRatio
+{
+    fn default() -> Self
+    {
+        Self{height: 2, width: 3}
+    }
+}
+
+impl AspectRatio
+{
+    pub fn parse(text: &str) -> Option<Self>
+    {
+        let (height, width) = text.split_once(':')?;
+
+        let height = height.parse().ok().filter(|x| *x > 0)?;
+        let width = width.parse().ok().filter(|x| *x > 0)?;
+
+        Some(Self{height, width})
+    }
+
+    /// width and height of a flag `height` pixels tall
+    pub fn size(&self, height: u32) -> (u32, u32)
+    {
+        let width = (height as f32 * self.width as f32 / self.height as f32).round() as u32;
+
+        (width.max(1), height)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ViewerOptions
+{
+    pub spec: Option<PathBuf>,
+    pub aspect: AspectRatio,
+    /// height of the saved flag in pixels
+    pub resolution: u32,
+    /// amount of specs kept for going back
+    pub history: usize,
+    pub out_dir: PathBuf,
+    /// favorites or a manifest to fill the history with
+    pub browse: Option<PathBuf>,
+    pub shared: SharedOptions
+}
+
+impl Default for ViewerOptions
+{
+    fn default() -> Self
+    {
+        Self{
+            spec: None,
+            aspect: AspectRatio::default(),
+            resolution: 400,
+            history: 100,
+            out_dir: PathBuf::from("saved"),
+            browse: None,
+            shared: SharedOptions::default()
+        }
+    }
+}
+
+impl ViewerOptions
+{
+    /// width and height the flag is saved at, the viewer shows it with the same proportions at any window size
+    pub fn flag_size(&self) -> (u32, u32)
+    {
+        self.aspect.size(self.resolution)
+    }
+
+    pub fn favorites_path(&self) -> PathBuf
+    {
+        self.out_dir.join("favorites.json")
+    }
+
+    /// writes the flag and its spec under a name no other saved flag has, returns the flag's path
+    pub fn save(&self, spec: &FlagSpec) -> Result<PathBuf, CliError>
+    {
+        fs::create_dir_all(&self.out_dir)?;
+
+        let base = spec.seed.map(|seed| format!("flag_{seed}")).unwrap_or_else(|| "flag".to_owned());
+        let extension = self.shared.format.extension();
+
+        let name = (0..).map(|index|
+        {
+            if index == 0 { base.clone() } else { format!("{base}_{index}") }
+        }).find(|name|
+        {
+            !self.out_dir.join(format!("{name}.{extension}")).exists()
+                && !self.out_dir.join(format!("{name}.json")).exists()
+        }).unwrap();
+
+        let (width, height) = self.flag_size();
+
+        let path = self.out_dir.join(format!("{name}.{extension}"));
+        self.shared.format.write(spec, &path, width, height)?;
+
+        if self.shared.simulate
+        {
+            Deficiency::ALL.into_iter().try_for_each(|deficiency|
+            {
+                let path = self.out_dir.join(format!("{name}_{deficiency}.{extension}"));
+                self.shared.format.write_simulated(spec, deficiency, path, width, height)
+            })?;
+        }
+
+        spec.save(self.out_dir.join(format!("{name}.json")))?;
+
+        Ok(path)
+    }
+
+    /// adds the spec to the end of the favorites file, creating it if needed
+    pub fn add_favorite(&self, spec: &FlagSpec) -> Result<PathBuf, CliError>
+    {
+        fs::create_dir_all(&self.out_dir)?;
+
+        let path = self.favorites_path();
+
+        let mut flags = if path.exists()
+        {
+            let favorites = Value::parse(&fs::read_to_string(&path)?).map_err(SpecError::from)?;
+
+            let flags = field(&favorites, "flags")?;
+
+            flags.as_array().ok_or_else(|| invalid("flags", flags))?.to_vec()
+        } else
+        {
+            Vec::new()
+        };
+
+        flags.push(Value::object([("spec", spec.to_value())]));
+
+        let favorites = Value::object([
+            ("version", SPEC_VERSION.into()),
+            ("flags", Value::Array(flags))
+        ]);
+
+        write_value(&path, &favorites)?;
+
+        Ok(path)
+    }
+
+    pub fn parse(mut args: impl Iterator<Item=String>) -> Result<Self, CliError>
+    {
+        let mut options = Self::default();
+
+        while let Some(arg) = args.next()
+        {
+            let mut value = ||
+            {
+                args.next().ok_or_else(|| CliError::Usage(format!("{arg} needs a value")))
+            };
+
+            let invalid = |value: &str|
+            {
+                CliError::Usage(format!("invalid value for {arg}: {value}"))
+            };
+
+            if options.shared.parse_arg(&arg, &mut value, invalid)?
+            {
+                continue;
+            }
+
+            match arg.as_str()
+            {
+                "--aspect" =>
+                {
+                    let value = value()?;
+                    options.aspect = AspectRatio::parse(&value).ok_or_else(|| invalid(&value))?;
+                },
+                "--resolution" =>
+                {
+                    let value = value()?;
+                    options.resolution = value.parse().ok().filter(|x| *x > 0).ok_or_else(|| invalid(&value))?;
+                },
+                "--history" =>
+                {
+                    let value = value()?;
+                    options.history = value.parse().ok().filter(|x| *x > 0).ok_or_else(|| invalid(&value))?;
+                },
+                "--out-dir" =>
+                {
+                    options.out_dir = PathBuf::from(value()?);
+                },
+                "--browse" =>
+                {
+                    options.browse = Some(PathBuf::from(value()?));
+                },
+                _ if arg.starts_with("--") => return Err(CliError::Usage(format!("unknown argument {arg}"))),
+                _ if options.spec.is_none() => options.spec = Some(PathBuf::from(arg)),
+                _ => return Err(CliError::Usage(format!("unexpected argument {arg}")))
+            }
+        }
+
+        options.shared.check()?;
+
+        Ok(options)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GenerateOptions
+{
+    pub count: usize,
+    pub width: u32,
+    pub height: u32,
+    pub out_dir: PathBuf,
+    pub seed: Option<u64>,
+    pub shared: SharedOptions
+}
+
+impl Default for GenerateOptions
+{
+    fn default() -> Self
+    {
+        Self{
+            count: 1,
+            width: 640,
+            height: 360,
+            out_dir: PathBuf::from("flags"),
+            seed: None,
+            shared: SharedOptions::default()
+        }
+    }
+}
+
+/// every spec in a favorites file or a manifest written by generate
+pub fn load_favorites(path: impl AsRef<Path>) -> Result<Vec<FlagSpec>, CliError>
+{
+    let value = Value::parse(&fs::read_to_string(path)?).map_err(SpecError::from)?;
+
+    let flags = field(&value, "flags")?;
+
+    let specs = flags.as_array().ok_or_else(|| invalid("flags", flags))?.iter().map(|flag|
+    {
+        FlagSpec::from_value(field(flag, "spec")?)
+    }).collect::<Result<_, _>>()?;
+
+    Ok(specs)
+}
+
+fn parse_size(text: &str) -> Option<(u32, u32)>
+{
+    let (width, height) = text.split_once('x')?;
+
+    let width = width.parse().ok().filter(|x| *x > 0)?;
+    let height = height.parse().ok().filter(|x| *x > 0)?;
+
+    Some((width, height))
+}
+
+/// relative widths like 1:2:1
+fn parse_weights(text: &str) -> Option<Vec<f32>>
+{
+    text.split(':').map(|weight|
+    {
+        weight.parse().ok().filter(|x: &f32| x.is_finite() && *x > 0.0)
+    }).collect()
+}
+
+fn parse_harmonies(text: &str) -> Option<Vec<Harmony>>
+{
+    if text == "any"
+    {
+        Some(Harmony::ALL.to_vec())
+    } else
+    {
+        Harmony::parse(text).map(|harmony| vec![harmony])
+    }
+}
+
+impl GenerateOptions
+{
+    pub fn parse(mut args: impl Iterator<Item=String>) -> Result<Self, CliError>
+    {
+        let mut options = Self::default();
+
+        while let Some(arg) = args.next()
+        {
+            let mut value = ||
+            {
+                args.next().ok_or_else(|| CliError::Usage(format!("{arg} needs a value")))
+            };
+
+            let invalid = |value: &str|
+            {
+                CliError::Usage(format!("invalid value for {arg}: {value}"))
+            };
+
+            if options.shared.parse_arg(&arg, &mut value, invalid)?
+            {
+                continue;
+            }
+
+            match arg.as_str()
+            {
+                "--count" =>
+                {
+                    let value = value()?;
+                    options.count = value.parse().map_err(|_| invalid(&value))?;
+                },
+                "--size" =>
+                {
+                    let value = value()?;
+                    (options.width, options.height) = parse_size(&value).ok_or_else(|| invalid(&value))?;
+                },
+                "--out-dir" =>
+                {
+                    options.out_dir = PathBuf::from(value()?);
+                },
+                "--seed" =>
+                {
+                    let value = value()?;
+                    options.seed = Some(value.parse().map_err(|_| invalid(&value))?);
+                },
+                _ => return Err(CliError::Usage(format!("unknown argument {arg}")))
+            }
+        }
+
+        options.shared.check()?;
+
+        Ok(options)
+    }
+}
+
+pub fn generate(options: &GenerateOptions) -> Result<(), CliError>
+{
+    fs::create_dir_all(&options.out_dir)?;
+
+    let seed = options.seed.unwrap_or_else(|| fastrand::u64(..));
+    let mut rng = Rng::with_seed(seed);
+
+    let digits = options.count.saturating_sub(1).to_string().len();
+
+    let flags = (0..options.count).map(|index|
+    {
+        let spec = FlagSpec::seeded_with(rng.u64(..), &options.shared.config);
+
+        let extension = options.shared.format.extension();
+
+        let name = format!("flag_{index:0digits$}.{extension}");
+        options.shared.format.write(&spec, options.out_dir.join(&name), options.width, options.height)?;
+
+        let mut fields = vec![("file", name.into())];
+
+        if options.shared.simulate
+        {
+            let simulated = Deficiency::ALL.into_iter().map(|deficiency|
+            {
+                let name = format!("flag_{index:0digits$}_{deficiency}.{extension}");
+
+                options.shared.format.write_simulated(
+                    &spec,
+                    deficiency,
+                    options.out_dir.join(&name),
+                    options.width,
+                    options.height
+                )?;
+
+                Ok((deficiency.to_string(), name.into()))
+            }).collect::<Result<Vec<_>, CliError>>()?;
+
+            fields.push(("simulated", Value::Object(simulated)));
+        }
+
+        fields.push(("spec", spec.to_value()));
+
+        Ok(Value::object(fields))
+    }).collect::<Result<Vec<_>, CliError>>()?;
+
+    let manifest = Value::object([
+        ("version", SPEC_VERSION.into()),
+        ("seed", seed.into()),
+        ("width", options.width.into()),
+        ("height", options.height.into()),
+        ("flags", Value::Array(flags))
+    ]);
+
+    write_value(options.out_dir.join("manifest.json"), &manifest)?;
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use std::{
+    fmt::{self, Display},
+    f32::consts::TAU
+};
+
+use image::Rgb;
+
+use fastrand::Rng;
+
+use crate::{Palette, Tincture};
+
+
+/// srgb channel to linear light, both from 0 to 1
+pub fn to_linear(x: u8) -> f32
+{
+    let x = x as f32 / u8::MAX as f32;
+
+    if x <= 0.04045 { x / 12.92 } else { ((x + 0.055) / 1.055).powf(2.4) }
+}
+
+/// linear light to an srgb channel, clamping anything out of range
+pub fn from_linear(x: f32) -> u8
+{
+    let x = if x <= 0.0031308 { x * 12.92 } else { 1.055 * x.powf(1.0 / 2.4) - 0.055 };
+
+    (x.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8
+}
+
+/// perceptual color space where distances roughly match how different colors look
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklab
+{
+    pub l: f32,
+    pub a: f32,
+    pub b: f32
+}
+
+impl Oklab
+{
+    pub fn distance(&self, other: Oklab) -> f32
+    {
+        ((self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2)).sqrt()
+    }
+
+    fn to_linear_rgb(self) -> [f32; 3]
+    {
+        let l = (self.l + 0.39633778 * self.a + 0.21580376 * self.b).powi(3);
+        let m = (self.l - 0.10556135 * self.a - 0.06385417 * self.b).powi(3);
+        let s = (self.l - 0.08948418 * self.a - 1.2914855 * self.b).powi(3);
+
+        [
+            4.0767417 * l - 3.3077116 * m + 0.23096993 * s,
+            -1.268438 * l + 2.6097574 * m - 0.34131938 * s,
+            -0.0041960863 * l - 0.7034186 * m + 1.7076147 * s
+        ]
+    }
+
+    pub fn in_gamut(&self) -> bool
+    {
+        self.to_linear_rgb().iter().all(|x| (-0.0001..=1.0001).contains(x))
+    }
+}
+
+impl From<Rgb<u8>> for Oklab
+{
+    fn from(color: Rgb<u8>) -> Self
+    {
+        let [r, g, b] = color.0.map(to_linear);
+
+        let l = (0.41222147 * r + 0.53633254 * g + 0.051445993 * b).cbrt();
+        let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+        let s = (0.08830246 * r + 0.28171884 * g + 0.6299787 * b).cbrt();
+
+        Self{
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            a: 1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            b: 0.025904037 * l + 0.78277177 * m - 0.80867577 * s
+        }
+    }
+}
+
+impl From<Oklab> for Rgb<u8>
+{
+    fn from(color: Oklab) -> Self
+    {
+        Rgb(color.to_linear_rgb().map(from_linear))
+    }
+}
+
+/// oklab in polar coordinates, `h` is in radians
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklch
+{
+    pub l: f32,
+    pub c: f32,
+    pub h: f32
+}
+
+impl Oklch
+{
+    /// lowers the chroma until the color fits in srgb
+    pub fn to_rgb(self) -> Rgb<u8>
+    {
+        if Oklab::from(self).in_gamut()
+        {
+            return Oklab::from(self).into();
+        }
+
+        let mut low = 0.0;
+        let mut high = self.c;
+
+        (0..16).for_each(|_|
+        {
+            let middle = (low + high) / 2.0;
+
+            if Oklab::from(Self{c: middle, ..self}).in_gamut()
+            {
+                low = middle;
+            } else
+            {
+                high = middle;
+            }
+        });
+
+        Oklab::from(Self{c: low, ..self}).into()
+    }
+}
+
+impl From<Oklch> for Oklab
+{
+    fn from(color: Oklch) -> Self
+    {
+        Self{l: color.l, a: color.c * color.h.cos(), b: color.c * color.h.sin()}
+    }
+}
+
+impl From<Oklab> for Oklch
+{
+    fn from(color: Oklab) -> Self
+    {
+        Self{l: color.l, c: color.a.hypot(color.b), h: color.b.atan2(color.a)}
+    }
+}
+
+/// perceptual distance between 2 colors, 0 for the same color and about 1 for black and white
+pub fn color_distance(a: Rgb<u8>, b: Rgb<u8>) -> f32
+{
+    Oklab::from(a).distance(Oklab::from(b))
+}
+
+/// hues that go well together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Harmony
+{
+    Complementary,
+    Triadic,
+    Analogous,
+    SplitComplementary
+}
+
+impl Display for Harmony
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let name = match self
+        {
+            Self::Complementary => "complementary",
+            Self::Triadic => "triadic",
+            Self::Analogous => "analogous",
+            Self::SplitComplementary => "split_complementary"
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+impl Harmony
+{
+    pub const ALL: [Self; 4] = [Self::Complementary, Self::Triadic, Self::Analogous, Self::SplitComplementary];
+
+    pub fn parse(text: &str) -> Option<Self>
+    {
+        Self::ALL.into_iter().find(|harmony| harmony.to_string() == text)
+    }
+
+    /// offsets of each hue from the base hue in radians
+    pub fn offsets(&self) -> Vec<f32>
+    {
+        let offsets: &[f32] = match self
+        {
+            Self::Complementary => &[0.0, 0.5],
+            Self::Triadic => &[0.0, 1.0 / 3.0, 2.0 / 3.0],
+            Self::Analogous => &[-1.0 / 12.0, 0.0, 1.0 / 12.0],
+            Self::SplitComplementary => &[0.0, 5.0 / 12.0, 7.0 / 12.0]
+        };
+
+        offsets.iter().map(|x| x * TAU).collect()
+    }
+
+    /// a dark and a light color for each hue around a random base hue, with white and a near black
+    pub fn random_palette(&self, rng: &mut Rng) -> Palette
+    {
+        let base = rng.f32() * TAU;
+
+        let mut tinctures: Vec<_> = self.offsets().into_iter().enumerate().flat_map(|(index, offset)|
+        {
+            let h = base + offset;
+
+            let dark = Oklch{l: rng.f32() * 0.15 + 0.45, c: rng.f32() * 0.08 + 0.12, h};
+            let light = Oklch{l: rng.f32() * 0.08 + 0.84, c: rng.f32() * 0.06 + 0.08, h};
+
+            [
+                Tincture::colour(format!("{self} {index}"), dark.to_rgb()),
+                Tincture::metal(format!("{self} light {index}"), light.to_rgb())
+            ]
+        }).collect();
+
+        tinctures.push(Tincture::metal("white", Rgb([u8::MAX; 3])));
+        tinctures.push(Tincture::colour("black", Oklch{l: 0.2, c: 0.02, h: base}.to_rgb()));
+
+        Palette::new(self.to_string(), tinctures)
+    }
+}
@@ -0,0 +1,288 @@
+use nalgebra::Vector2;
+
+use fastrand::Rng;
+
+use crate::{
+    json::Value,
+    spec::{self, SpecValue, SpecError}
+};
+
+
+/// how a background is split between its colors
+///
+/// colors are picked in order and wrap around if there are fewer colors than parts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Division
+{
+    /// stripes going along the x axis if `horizontal`, sized by the background's weights
+    Stripes{horizontal: bool},
+    /// 3 stripes, the middle one `ratio` times as wide as the outer ones
+    Triband{horizontal: bool, ratio: f32},
+    /// a hoist half and a fly half
+    PerPale,
+    /// an upper half and a lower half
+    PerFess,
+    /// a band from the lower hoist to the upper fly between the upper hoist and the lower fly,
+    /// `width` is a fraction of the flag's shorter side, `sinister` mirrors it
+    Bend{width: f32, sinister: bool},
+    /// split by both diagonals into the hoist, upper, fly and lower parts
+    PerSaltire,
+    /// upper hoist, upper fly, lower fly and lower hoist quarters
+    Quartered,
+    /// a triangle from the hoist over rows of the other colors,
+    /// `depth` is how far it reaches as a fraction of the width
+    Chevron{depth: f32}
+}
+
+/// a part of a divided background, `color` indexes into the background's colors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region
+{
+    pub color: usize,
+    /// in pixels, reaching past the flag's edges so they dont get antialiased
+    pub points: Vec<Vector2<f32>>
+}
+
+impl Division
+{
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        match rng.usize(0..16)
+        {
+            0..=7 => Self::Stripes{horizontal: rng.bool()},
+            8..=9 => Self::Triband{horizontal: rng.bool(), ratio: rng.f32() * 1.5 + 1.5},
+            10 => Self::PerPale,
+            11 => Self::PerFess,
+            12 => Self::Bend{width: rng.f32() * 0.15 + 0.15, sinister: rng.bool()},
+            13 => Self::PerSaltire,
+            14 => Self::Quartered,
+            15 => Self::Chevron{depth: rng.f32() * 0.2 + 0.3},
+            _ => unreachable!()
+        }
+    }
+
+    /// amount of colors that fits the division
+    pub fn random_amount(&self, rng: &mut Rng) -> usize
+    {
+        match self
+        {
+            Self::Stripes{..} => rng.usize(1..6),
+            Self::Triband{..} | Self::Bend{..} => 3,
+            Self::PerPale | Self::PerFess => 2,
+            Self::PerSaltire | Self::Quartered => if rng.bool() { 2 } else { 4 },
+            Self::Chevron{..} => rng.usize(2..5)
+        }
+    }
+
+    /// amount of parts the flag is split into with `amount` colors
+    pub fn parts(&self, amount: usize) -> usize
+    {
+        match self
+        {
+            Self::Stripes{..} => amount,
+            Self::Triband{..} | Self::Bend{..} => 3,
+            Self::PerPale | Self::PerFess => 2,
+            Self::PerSaltire | Self::Quartered => 4,
+            Self::Chevron{..} => amount.max(2)
+        }
+    }
+
+    /// earlier parts that touch the `index`th part
+    pub fn neighbours(&self, index: usize) -> Vec<usize>
+    {
+        match self
+        {
+            Self::PerSaltire | Self::Quartered if index == 3 => vec![0, 2],
+            Self::Chevron{..} if index > 1 => vec![0, index - 1],
+            _ => index.checked_sub(1).into_iter().collect()
+        }
+    }
+
+    /// direction of the stripes and the fraction of the flag where each stripe ends,
+    /// if the division is made of stripes, plain stripes use `weights` as their relative widths
+    pub fn stripes(&self, weights: &[f32]) -> Option<(bool, Vec<f32>)>
+    {
+        let weighted = |horizontal, weights: &[f32]|
+        {
+            let total: f32 = weights.iter().sum();
+
+            let ends = weights.iter().scan(0.0, |end, weight|
+            {
+                *end += weight / total;
+
+                Some(*end)
+            }).collect();
+
+            Some((horizontal, ends))
+        };
+
+        match self
+        {
+            Self::Stripes{horizontal} => weighted(*horizontal, weights),
+            Self::Triband{horizontal, ratio} => weighted(*horizontal, &[1.0, *ratio, 1.0]),
+            Self::PerPale => weighted(true, &[1.0, 1.0]),
+            Self::PerFess => weighted(false, &[1.0, 1.0]),
+            _ => None
+        }
+    }
+
+    /// parts of a `size` flag drawn in order over the first color, empty for stripes
+    pub fn regions(&self, amount: usize, size: Vector2<f32>) -> Vec<Region>
+    {
+        let center = size / 2.0;
+
+        // far enough that every part reaches past the edges
+        let far = (size.x + size.y) * 2.0;
+
+        // triangle with its tip at `apex` going towards `a` and `b` and past them
+        let wedge = |color, apex: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>|
+        {
+            let reach = |point: Vector2<f32>| apex + (point - apex).normalize() * far;
+
+            Region{color, points: vec![apex, reach(a), reach(b)]}
+        };
+
+        match self
+        {
+            Self::Stripes{..} | Self::Triband{..} | Self::PerPale | Self::PerFess => Vec::new(),
+            Self::Bend{width, sinister} =>
+            {
+                let mirror = |point: Vector2<f32>| if *sinister { Vector2::new(size.x - point.x, point.y) } else { point };
+
+                let direction = Vector2::new(size.x, -size.y).normalize() * far;
+                let normal = Vector2::new(size.y, size.x).normalize();
+
+                let start = Vector2::new(0.0, size.y) - direction;
+                let end = Vector2::new(size.x, 0.0) + direction;
+
+                let half_width = normal * (width * size.x.min(size.y) / 2.0);
+
+                let fly = [start + half_width, end + half_width, end + normal * far, start + normal * far];
+                let band = [start - half_width, end - half_width, end + half_width, start + half_width];
+
+                vec![
+                    Region{color: 2, points: fly.into_iter().map(mirror).collect()},
+                    Region{color: 1, points: band.into_iter().map(mirror).collect()}
+                ]
+            },
+            Self::PerSaltire =>
+            {
+                let corner = |x: f32, y: f32| Vector2::new(x, y).component_mul(&size);
+
+                vec![
+                    wedge(1, center, corner(0.0, 0.0), corner(1.0, 0.0)),
+                    wedge(2, center, corner(1.0, 0.0), corner(1.0, 1.0)),
+                    wedge(3, center, corner(1.0, 1.0), corner(0.0, 1.0))
+                ]
+            },
+            Self::Quartered =>
+            {
+                let quarter = |color, x: f32, y: f32|
+                {
+                    let corner = center + Vector2::new(x, y) * far;
+
+                    Region{color, points: vec![
+                        center,
+                        Vector2::new(corner.x, center.y),
+                        corner,
+                        Vector2::new(center.x, corner.y)
+                    ]}
+                };
+
+                vec![quarter(1, 1.0, -1.0), quarter(2, 1.0, 1.0), quarter(3, -1.0, 1.0)]
+            },
+            Self::Chevron{depth} =>
+            {
+                let rows = amount.saturating_sub(1).max(1);
+
+                let mut regions: Vec<_> = (0..rows).map(|index|
+                {
+                    let edge = |index: usize|
+                    {
+                        if index == 0
+                        {
+                            -far
+                        } else if index == rows
+                        {
+                            far
+                        } else
+                        {
+                            size.y * index as f32 / rows as f32
+                        }
+                    };
+
+                    let (top, bottom) = (edge(index), edge(index + 1));
+
+                    Region{color: index + 1, points: vec![
+                        Vector2::new(-far, top),
+                        Vector2::new(far, top),
+                        Vector2::new(far, bottom),
+                        Vector2::new(-far, bottom)
+                    ]}
+                }).collect();
+
+                let apex = Vector2::new(depth * size.x, center.y);
+                regions.push(wedge(0, apex, Vector2::zeros(), Vector2::new(0.0, size.y)));
+
+                regions
+            }
+        }
+    }
+}
+
+impl SpecValue for Division
+{
+    fn to_value(&self) -> Value
+    {
+        match self
+        {
+            Self::Stripes{horizontal} =>
+            {
+                Value::object([("type", "stripes".into()), ("horizontal", (*horizontal).into())])
+            },
+            Self::Triband{horizontal, ratio} =>
+            {
+                Value::object([
+                    ("type", "triband".into()),
+                    ("horizontal", (*horizontal).into()),
+                    ("ratio", (*ratio).into())
+                ])
+            },
+            Self::PerPale => Value::object([("type", "per_pale".into())]),
+            Self::PerFess => Value::object([("type", "per_fess".into())]),
+            Self::Bend{width, sinister} =>
+            {
+                Value::object([
+                    ("type", "bend".into()),
+                    ("width", (*width).into()),
+                    ("sinister", (*sinister).into())
+                ])
+            },
+            Self::PerSaltire => Value::object([("type", "per_saltire".into())]),
+            Self::Quartered => Value::object([("type", "quartered".into())]),
+            Self::Chevron{depth} => Value::object([("type", "chevron".into()), ("depth", (*depth).into())])
+        }
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        Ok(match spec::str_field(value, "type")?
+        {
+            "stripes" => Self::Stripes{horizontal: spec::bool_field(value, "horizontal")?},
+            "triband" => Self::Triband{
+                horizontal: spec::bool_field(value, "horizontal")?,
+                ratio: spec::f32_in(value, "ratio", 0.1..=10.0)?
+            },
+            "per_pale" => Self::PerPale,
+            "per_fess" => Self::PerFess,
+            "bend" => Self::Bend{
+                width: spec::f32_in(value, "width", 0.01..=1.0)?,
+                sinister: spec::bool_field(value, "sinister")?
+            },
+            "per_saltire" => Self::PerSaltire,
+            "quartered" => Self::Quartered,
+            "chevron" => Self::Chevron{depth: spec::f32_in(value, "depth", 0.05..=1.0)?},
+            _ => return Err(spec::invalid("type", spec::field(value, "type")?))
+        })
+    }
+}
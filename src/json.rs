@@ -0,0 +1,539 @@
+use std::fmt::{self, Display, Write};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value
+{
+    Null,
+    Bool(bool),
+    // kept as text so integers like seeds dont lose precision
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>)
+}
+
+impl From<bool> for Value
+{
+    fn from(value: bool) -> Self
+    {
+        Self::Bool(value)
+    }
+}
+
+impl From<f32> for Value
+{
+    fn from(value: f32) -> Self
+    {
+        Self::Number(value.to_string())
+    }
+}
+
+impl From<u32> for Value
+{
+    fn from(value: u32) -> Self
+    {
+        Self::Number(value.to_string())
+    }
+}
+
+impl From<u64> for Value
+{
+    fn from(value: u64) -> Self
+    {
+        Self::Number(value.to_string())
+    }
+}
+
+impl From<&str> for Value
+{
+    fn from(value: &str) -> Self
+    {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for Value
+{
+    fn from(value: String) -> Self
+    {
+        Self::String(value)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value
+{
+    fn from(value: Vec<T>) -> Self
+    {
+        Self::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl Value
+{
+    pub fn object(fields: impl IntoIterator<Item=(&'static str, Value)>) -> Self
+    {
+        Self::Object(fields.into_iter().map(|(key, value)| (key.to_owned(), value)).collect())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value>
+    {
+        match self
+        {
+            Self::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool>
+    {
+        match self
+        {
+            Self::Bool(x) => Some(*x),
+            _ => None
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32>
+    {
+        match self
+        {
+            Self::Number(x) => x.parse().ok(),
+            _ => None
+        }
+    }
+
+    pub fn as_u32(&self) -> Option<u32>
+    {
+        match self
+        {
+            Self::Number(x) => x.parse().ok(),
+            _ => None
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64>
+    {
+        match self
+        {
+            Self::Number(x) => x.parse().ok(),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str>
+    {
+        match self
+        {
+            Self::String(x) => Some(x),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]>
+    {
+        match self
+        {
+            Self::Array(x) => Some(x),
+            _ => None
+        }
+    }
+
+    /// text of the first number that isnt finite, which json has no way to write
+    pub fn non_finite(&self) -> Option<&str>
+    {
+        match self
+        {
+            Self::Number(x) => x.parse::<f64>().map_or(true, |x| !x.is_finite()).then_some(x.as_str()),
+            Self::Array(values) => values.iter().find_map(Self::non_finite),
+            Self::Object(fields) => fields.iter().find_map(|(_, value)| value.non_finite()),
+            _ => None
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ParseError>
+    {
+        let mut parser = Parser{text, position: 0, depth: 0};
+
+        let value = parser.value()?;
+
+        parser.skip_whitespace();
+        if parser.position != text.len()
+        {
+            return Err(parser.error("trailing characters"));
+        }
+
+        Ok(value)
+    }
+
+    fn write_indented(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result
+    {
+        let indent = |f: &mut fmt::Formatter, depth: usize|
+        {
+            (0..depth * 4).try_for_each(|_| f.write_char(' '))
+        };
+
+        match self
+        {
+            Self::Null => f.write_str("null"),
+            Self::Bool(x) => write!(f, "{x}"),
+            Self::Number(x) => f.write_str(x),
+            Self::String(x) => write_string(f, x),
+            Self::Array(values) =>
+            {
+                if values.is_empty()
+                {
+                    return f.write_str("[]");
+                }
+
+                f.write_str("[\n")?;
+                values.iter().enumerate().try_for_each(|(index, value)|
+                {
+                    indent(f, depth + 1)?;
+                    value.write_indented(f, depth + 1)?;
+
+                    f.write_str(if index + 1 == values.len() { "\n" } else { ",\n" })
+                })?;
+
+                indent(f, depth)?;
+                f.write_char(']')
+            },
+            Self::Object(fields) =>
+            {
+                if fields.is_empty()
+                {
+                    return f.write_str("{}");
+                }
+
+                f.write_str("{\n")?;
+                fields.iter().enumerate().try_for_each(|(index, (key, value))|
+                {
+                    indent(f, depth + 1)?;
+                    write_string(f, key)?;
+                    f.write_str(": ")?;
+                    value.write_indented(f, depth + 1)?;
+
+                    f.write_str(if index + 1 == fields.len() { "\n" } else { ",\n" })
+                })?;
+
+                indent(f, depth)?;
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, text: &str) -> fmt::Result
+{
+    f.write_char('"')?;
+
+    text.chars().try_for_each(|c|
+    {
+        match c
+        {
+            '"' => f.write_str("\\\""),
+            '\\' => f.write_str("\\\\"),
+            '\n' => f.write_str("\\n"),
+            '\t' => f.write_str("\\t"),
+            '\r' => f.write_str("\\r"),
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32),
+            c => f.write_char(c)
+        }
+    })?;
+
+    f.write_char('"')
+}
+
+impl Display for Value
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.write_indented(f, 0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseError
+{
+    pub position: usize,
+    pub message: String
+}
+
+impl Display for ParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{} at byte {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// arrays and objects nested deeper than this are rejected instead of overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a>
+{
+    text: &'a str,
+    position: usize,
+    /// arrays and objects currently open
+    depth: usize
+}
+
+impl Parser<'_>
+{
+    fn error(&self, message: impl Into<String>) -> ParseError
+    {
+        ParseError{position: self.position, message: message.into()}
+    }
+
+    fn peek(&self) -> Option<char>
+    {
+        self.text[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char>
+    {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self)
+    {
+        while self.peek().map(|c| c.is_whitespace()).unwrap_or(false)
+        {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError>
+    {
+        self.skip_whitespace();
+
+        match self.next()
+        {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!("expected '{expected}', found '{c}'"))),
+            None => Err(self.error(format!("expected '{expected}', found end of input")))
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> Result<Value, ParseError>
+    {
+        if self.text[self.position..].starts_with(keyword)
+        {
+            self.position += keyword.len();
+
+            Ok(value)
+        } else
+        {
+            Err(self.error("unexpected token"))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError>
+    {
+        self.skip_whitespace();
+
+        match self.peek()
+        {
+            Some('n') => self.keyword("null", Value::Null),
+            Some('t') => self.keyword("true", Value::Bool(true)),
+            Some('f') => self.keyword("false", Value::Bool(false)),
+            Some('"') => self.string().map(Value::String),
+            Some('[') => self.nested(Self::array),
+            Some('{') => self.nested(Self::object),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(self.error(format!("unexpected character '{c}'"))),
+            None => Err(self.error("unexpected end of input"))
+        }
+    }
+
+    fn nested(&mut self, parse: impl FnOnce(&mut Self) -> Result<Value, ParseError>) -> Result<Value, ParseError>
+    {
+        if self.depth >= MAX_DEPTH
+        {
+            return Err(self.error(format!("nested deeper than {MAX_DEPTH} levels")));
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError>
+    {
+        let start = self.position;
+
+        while self.peek().map(|c| c.is_ascii_digit() || "+-.eE".contains(c)).unwrap_or(false)
+        {
+            self.next();
+        }
+
+        let text = &self.text[start..self.position];
+        if text.parse::<f64>().is_err()
+        {
+            return Err(ParseError{position: start, message: format!("invalid number {text}")});
+        }
+
+        Ok(Value::Number(text.to_owned()))
+    }
+
+    fn string(&mut self) -> Result<String, ParseError>
+    {
+        self.expect('"')?;
+
+        let mut output = String::new();
+        loop
+        {
+            match self.next()
+            {
+                Some('"') => return Ok(output),
+                Some('\\') =>
+                {
+                    let c = match self.next()
+                    {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') =>
+                        {
+                            let digits = self.text.get(self.position..self.position + 4)
+                                .ok_or_else(|| self.error("unfinished unicode escape"))?;
+
+                            let c = u32::from_str_radix(digits, 16).ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+
+                            self.position += 4;
+
+                            c
+                        },
+                        _ => return Err(self.error("invalid escape"))
+                    };
+
+                    output.push(c);
+                },
+                Some(c) => output.push(c),
+                None => return Err(self.error("unterminated string"))
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError>
+    {
+        self.expect('[')?;
+
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']')
+        {
+            self.next();
+
+            return Ok(Value::Array(values));
+        }
+
+        loop
+        {
+            values.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.next()
+            {
+                Some(',') => (),
+                Some(']') => return Ok(Value::Array(values)),
+                _ => return Err(self.error("expected ',' or ']'"))
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, ParseError>
+    {
+        self.expect('{')?;
+
+        let mut fields = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}')
+        {
+            self.next();
+
+            return Ok(Value::Object(fields));
+        }
+
+        loop
+        {
+            self.skip_whitespace();
+            let key = self.string()?;
+
+            self.expect(':')?;
+
+            fields.push((key, self.value()?));
+
+            self.skip_whitespace();
+            match self.next()
+            {
+                Some(',') => (),
+                Some('}') => return Ok(Value::Object(fields)),
+                _ => return Err(self.error("expected ',' or '}'"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    #[test]
+    fn round_trip()
+    {
+        let value = Value::object([
+            ("null", Value::Null),
+            ("bool", true.into()),
+            ("float", 0.25_f32.into()),
+            ("seed", u64::MAX.into()),
+            ("text", "quote \" slash \\ newline \n tab \t bell \u{7} ü".into()),
+            ("empty", Value::Array(Vec::new())),
+            ("nested", Value::Array(vec![Value::object([]), vec![1_u32, 2, 3].into()]))
+        ]);
+
+        let text = value.to_string();
+
+        assert_eq!(Value::parse(&text).unwrap(), value);
+        assert_eq!(Value::parse(&text).unwrap().to_string(), text);
+    }
+
+    #[test]
+    fn rejects_deep_nesting()
+    {
+        let text = "[".repeat(100_000);
+
+        assert!(Value::parse(&text).is_err());
+
+        let fine = "[".repeat(MAX_DEPTH) + &"]".repeat(MAX_DEPTH);
+        assert!(Value::parse(&fine).is_ok());
+    }
+
+    #[test]
+    fn finds_non_finite()
+    {
+        let value = Value::object([("a", 1.0_f32.into()), ("b", vec![f32::NAN].into())]);
+        assert_eq!(value.non_finite(), Some("NaN"));
+
+        assert_eq!(Value::from(f32::INFINITY).non_finite(), Some("inf"));
+        assert_eq!(Value::from(1.5_f32).non_finite(), None);
+    }
+}
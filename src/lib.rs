@@ -0,0 +1,801 @@
+#![allow(clippy::suspicious_else_formatting)]
+
+use std::ops::RangeInclusive;
+
+use nalgebra::Vector2;
+
+use image::{RgbImage, Rgb};
+
+use fastrand::Rng;
+
+pub use spec::{FlagSpec, FlagBuilder};
+pub use shape::{FlagShape, RegisteredShape, FlagForegroundShape, ShapeRegistry, Fimbriation};
+pub use star::{Star, StarArrangement};
+pub use celestial::{Crescent, Sun};
+pub use nordic::NordicCross;
+pub use division::{Division, Region};
+pub use palette::{Palette, Tincture};
+pub use color::{Oklab, Oklch, Harmony, color_distance};
+
+pub mod json;
+pub mod spec;
+pub mod svg;
+pub mod shape;
+pub mod star;
+pub mod celestial;
+pub mod nordic;
+pub mod division;
+pub mod palette;
+pub mod color;
+pub mod analysis;
+pub mod vision;
+
+
+/// settings for generating random flags
+#[derive(Debug, Clone)]
+pub struct RandomConfig
+{
+    pub shapes: ShapeRegistry,
+    /// chance of a flag having a canton
+    pub canton_chance: f32,
+    /// makes every random background stripes with these relative widths
+    pub stripes: Option<Vec<f32>>,
+    /// colors are picked from this instead of any rgb color
+    pub palette: Option<Palette>,
+    /// keeps metals off metals and colours off colours, only used with a palette
+    pub tincture: bool,
+    /// each flag takes its colors from a random scheme of one of these, replacing `palette` if both are set
+    pub harmonies: Vec<Harmony>,
+    /// perceptual distance colors keep from the colors next to them, see `color_distance`
+    pub min_distance: f32,
+    /// `FlagSpec::random_checked` rerolls flags with a layer below this contrast ratio against what it covers
+    pub min_layer_contrast: f32,
+    /// `FlagSpec::random_checked` rerolls flags with touching background parts below this contrast ratio,
+    /// contrast ratios start at 1 so 1 turns the check off
+    pub min_neighbour_contrast: f32,
+    /// layers with a lower contrast ratio than this against a color under them get outlined
+    pub outline_contrast: f32
+}
+
+impl Default for RandomConfig
+{
+    fn default() -> Self
+    {
+        Self{
+            shapes: ShapeRegistry::default(),
+            canton_chance: 0.15,
+            stripes: None,
+            palette: None,
+            tincture: false,
+            harmonies: Vec::new(),
+            min_distance: 0.08,
+            min_layer_contrast: 1.5,
+            min_neighbour_contrast: 1.25,
+            outline_contrast: 1.5
+        }
+    }
+}
+
+impl RandomConfig
+{
+    /// a color that goes next to the `next_to` colors
+    pub fn color(&self, rng: &mut Rng, next_to: &[Rgb<u8>]) -> Rgb<u8>
+    {
+        if let Some(palette) = &self.palette
+        {
+            return palette.random(rng, next_to, self.tincture, self.min_distance);
+        }
+
+        let distinct = |color: Rgb<u8>|
+        {
+            next_to.iter().all(|other| color_distance(color, *other) >= self.min_distance)
+        };
+
+        // gives up eventually so a huge distance cant hang
+        let mut color = random_color(rng);
+        for _ in 0..32
+        {
+            if distinct(color)
+            {
+                break;
+            }
+
+            color = random_color(rng);
+        }
+
+        color
+    }
+
+    /// picks a scheme from `harmonies` and uses it as the palette in place of `palette`, if there are any
+    pub fn harmonized(&self, rng: &mut Rng) -> Self
+    {
+        if self.harmonies.is_empty()
+        {
+            return self.clone();
+        }
+
+        let harmony = self.harmonies[rng.usize(0..self.harmonies.len())];
+
+        Self{
+            palette: Some(harmony.random_palette(rng)),
+            harmonies: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FlagBackground
+{
+    pub division: Division,
+    /// colors of the parts of the division, must not be empty
+    pub lines: Vec<Rgb<u8>>,
+    /// relative widths of plain stripes, one for each line, equal if `None`
+    pub weights: Option<Vec<f32>>
+}
+
+pub fn random_color(rng: &mut Rng) -> Rgb<u8>
+{
+    let mut r = ||
+    {
+        rng.u8(0..=u8::MAX)
+    };
+
+    Rgb([r(), r(), r()])
+}
+
+pub fn blend(a: Rgb<u8>, b: Rgb<u8>, amount: f32) -> Rgb<u8>
+{
+    Rgb(std::array::from_fn(|index|
+    {
+        let (a, b) = (a.0[index] as f32, b.0[index] as f32);
+
+        (a + (b - a) * amount).round() as u8
+    }))
+}
+
+/// signed distance to a closed polygon, negative inside
+pub fn polygon_distance(pos: Vector2<f32>, points: &[Vector2<f32>]) -> f32
+{
+    let mut distance = f32::MAX;
+    let mut sign = 1.0;
+
+    (0..points.len()).for_each(|index|
+    {
+        let current = points[index];
+        let previous = points[(index + points.len() - 1) % points.len()];
+
+        let edge = previous - current;
+        let offset = pos - current;
+
+        let along = (offset.dot(&edge) / edge.dot(&edge)).clamp(0.0, 1.0);
+        distance = distance.min((offset - edge * along).magnitude_squared());
+
+        let crossings = [
+            pos.y >= current.y,
+            pos.y < previous.y,
+            edge.x * offset.y > edge.y * offset.x
+        ];
+
+        if crossings.iter().all(|x| *x) || crossings.iter().all(|x| !*x)
+        {
+            sign = -sign;
+        }
+    });
+
+    sign * distance.sqrt()
+}
+
+/// relative luminance as defined by wcag, from 0 for black to 1 for white
+pub fn luminance(color: Rgb<u8>) -> f32
+{
+    let [r, g, b] = color.0.map(color::to_linear);
+
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// moves a parameter by `steps` steps of 5% of its size, but at least 0.01
+pub fn step_parameter(x: f32, steps: i32) -> f32
+{
+    x + steps as f32 * (x.abs() * 0.05).max(0.01)
+}
+
+/// wcag contrast ratio, from 1 for the same colors to 21 for black on white
+pub fn contrast_ratio(a: Rgb<u8>, b: Rgb<u8>) -> f32
+{
+    let (a, b) = (luminance(a), luminance(b));
+
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+pub fn hex_color(color: Rgb<u8>) -> String
+{
+    let Rgb([r, g, b]) = color;
+
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+impl FlagBackground
+{
+    /// equal stripes going along the x axis if `horizontal`
+    pub fn new(horizontal: bool, lines: Vec<Rgb<u8>>) -> Self
+    {
+        Self::divided(Division::Stripes{horizontal}, lines)
+    }
+
+    pub fn divided(division: Division, lines: Vec<Rgb<u8>>) -> Self
+    {
+        assert!(!lines.is_empty(), "background must have at least one line");
+
+        Self{division, lines, weights: None}
+    }
+
+    pub fn with_weights(mut self, weights: Vec<f32>) -> Self
+    {
+        assert_eq!(weights.len(), self.lines.len(), "background must have a weight for every line");
+
+        self.weights = Some(weights);
+
+        self
+    }
+
+    pub fn solid(color: Rgb<u8>) -> Self
+    {
+        Self::new(false, vec![color])
+    }
+
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        Self::random_with(rng, &RandomConfig::default())
+    }
+
+    pub fn random_with(rng: &mut Rng, config: &RandomConfig) -> Self
+    {
+        if let Some(weights) = &config.stripes
+        {
+            let division = Division::Stripes{horizontal: rng.bool()};
+            let lines = Self::random_lines(rng, config, division, weights.len());
+
+            return Self::divided(division, lines).with_weights(weights.clone());
+        }
+
+        let division = Division::random(rng);
+
+        let amount = division.random_amount(rng);
+        let lines = Self::random_lines(rng, config, division, amount);
+
+        let background = Self::divided(division, lines);
+
+        if background.is_striped() && amount > 2 && rng.usize(0..3) == 0
+        {
+            let weights = Self::random_symmetric_weights(rng, amount);
+
+            background.with_weights(weights)
+        } else
+        {
+            background
+        }
+    }
+
+    /// weights that mirror around the middle, like 1:2:1 or 1:1:2:1:1
+    pub fn random_symmetric_weights(rng: &mut Rng, amount: usize) -> Vec<f32>
+    {
+        let mut half: Vec<f32> = (0..amount.div_ceil(2)).map(|_|
+        {
+            if rng.usize(0..3) == 0 { 2.0 } else { 1.0 }
+        }).collect();
+
+        // equal stripes would look like they have no weights at all
+        if half.iter().all(|x| *x == half[0])
+        {
+            *half.last_mut().unwrap() = half[0] * 2.0;
+        }
+
+        (0..amount).map(|index| half[index.min(amount - 1 - index)]).collect()
+    }
+
+    /// only equal stripes
+    pub fn random_stripes(rng: &mut Rng, config: &RandomConfig) -> Self
+    {
+        let amount = rng.usize(1..6);
+        let division = Division::Stripes{horizontal: rng.bool()};
+
+        Self::divided(division, Self::random_lines(rng, config, division, amount))
+    }
+
+    /// colors for each part, each picked to go next to the parts it touches
+    pub(crate) fn random_lines(rng: &mut Rng, config: &RandomConfig, division: Division, amount: usize) -> Vec<Rgb<u8>>
+    {
+        let mut lines = Vec::with_capacity(amount);
+
+        (0..amount).for_each(|index|
+        {
+            let next_to: Vec<_> = division.neighbours(index).into_iter().map(|x| lines[x]).collect();
+
+            lines.push(config.color(rng, &next_to));
+        });
+
+        lines
+    }
+
+    /// direction of the stripes and the fraction of the flag where each one ends,
+    /// if the background is made of stripes
+    pub fn stripes(&self) -> Option<(bool, Vec<f32>)>
+    {
+        match &self.weights
+        {
+            Some(weights) => self.division.stripes(weights),
+            None => self.division.stripes(&vec![1.0; self.lines.len()])
+        }
+    }
+
+    /// index of the part at `pos` in pixels on a flag `size` pixels big
+    pub fn part_at(&self, pos: Vector2<f32>, size: Vector2<f32>) -> usize
+    {
+        if let Some((horizontal, ends)) = self.stripes()
+        {
+            let fraction = if horizontal { pos.x / size.x } else { pos.y / size.y };
+
+            return ends.iter().position(|x| fraction < *x).unwrap_or(ends.len() - 1);
+        }
+
+        // later regions are drawn over earlier ones
+        self.division.regions(self.lines.len(), size).into_iter().rev()
+            .find(|region| polygon_distance(pos, &region.points) < 0.0)
+            .map(|region| region.color)
+            .unwrap_or(0)
+    }
+
+    /// a new color for the `index`th part that goes with every part touching it
+    pub fn recolor(&mut self, rng: &mut Rng, config: &RandomConfig, index: usize)
+    {
+        let touching = |other: usize|
+        {
+            self.division.neighbours(index).contains(&other) || self.division.neighbours(other).contains(&index)
+        };
+
+        let mut next_to: Vec<_> = (0..self.division.parts(self.lines.len()))
+            .filter(|other| touching(*other))
+            .map(|other| self.color(other))
+            .collect();
+
+        next_to.push(self.color(index));
+
+        let amount = self.lines.len();
+        self.lines[index % amount] = config.color(rng, &next_to);
+    }
+
+    pub fn is_striped(&self) -> bool
+    {
+        matches!(self.division, Division::Stripes{..})
+    }
+
+    /// color of the `index`th part, wrapping around
+    pub fn color(&self, index: usize) -> Rgb<u8>
+    {
+        self.lines[index % self.lines.len()]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FlagForeground
+{
+    pub color: Rgb<u8>,
+    pub shape: FlagForegroundShape,
+    /// offset of the shape's center as a fraction of the flag's size
+    pub position: Vector2<f32>,
+    /// scale of the shape around its center
+    pub scale: f32,
+    /// border around the shape and its fimbriation
+    pub outline: Option<Fimbriation>
+}
+
+impl FlagForeground
+{
+    /// scales a layer can be loaded or edited to, at 0 or below the shape turns inside out
+    pub const SCALES: RangeInclusive<f32> = 0.01..=10.0;
+
+    pub fn new(color: Rgb<u8>, shape: FlagForegroundShape) -> Self
+    {
+        Self{color, shape, position: Vector2::zeros(), scale: 1.0, outline: None}
+    }
+
+    pub fn with_position(mut self, position: Vector2<f32>) -> Self
+    {
+        self.position = position;
+
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self
+    {
+        self.scale = scale;
+
+        self
+    }
+
+    pub fn with_outline(mut self, outline: Fimbriation) -> Self
+    {
+        self.outline = Some(outline);
+
+        self
+    }
+
+    /// color of the shape's outer edge, before the outline
+    pub fn edge_color(&self) -> Rgb<u8>
+    {
+        self.shape.fimbriation().map(|x| x.color).unwrap_or(self.color)
+    }
+
+    /// outlines the layer in black or white if it's hard to tell apart from any of the `under` colors
+    pub fn outline_clashing(mut self, under: &[Rgb<u8>], contrast: f32) -> Self
+    {
+        let edge = self.edge_color();
+
+        let clashes = under.iter().any(|color| contrast_ratio(edge, *color) < contrast);
+        if self.outline.is_none() && clashes
+        {
+            let (black, white) = (Rgb([0, 0, 0]), Rgb([u8::MAX; 3]));
+            let color = if contrast_ratio(edge, black) > contrast_ratio(edge, white) { black } else { white };
+
+            self.outline = Some(Fimbriation{color, width: 0.015});
+        }
+
+        self
+    }
+
+    /// names of the numbers `adjust` can change, the layer's own and then its shape's
+    pub fn parameter_names(&self) -> Vec<&'static str>
+    {
+        let shape = self.shape.parameters().into_iter()
+            .filter(|(_, value)| value.as_f32().is_some())
+            .map(|(name, _)| name);
+
+        ["x", "y", "scale"].into_iter().chain(shape).collect()
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<f32>
+    {
+        match name
+        {
+            "x" => Some(self.position.x),
+            "y" => Some(self.position.y),
+            "scale" => Some(self.scale),
+            _ => self.shape.parameters().into_iter().find(|(x, _)| *x == name).and_then(|(_, value)| value.as_f32())
+        }
+    }
+
+    /// moves the parameter `name` by `steps` small steps, returns whether anything changed
+    pub fn adjust(&mut self, shapes: &ShapeRegistry, name: &str, steps: i32) -> bool
+    {
+        match name
+        {
+            "x" => self.position.x = step_parameter(self.position.x, steps),
+            "y" => self.position.y = step_parameter(self.position.y, steps),
+            "scale" => self.scale = step_parameter(self.scale, steps).clamp(*Self::SCALES.start(), *Self::SCALES.end()),
+            _ =>
+            {
+                match shapes.adjusted(&self.shape, name, steps)
+                {
+                    Some(shape) => self.shape = shape,
+                    None => return false
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn is_transformed(&self) -> bool
+    {
+        self.position != Vector2::zeros() || self.scale != 1.0
+    }
+
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        Self::random_with(rng, &RandomConfig::default())
+    }
+
+    pub fn random_with(rng: &mut Rng, config: &RandomConfig) -> Self
+    {
+        Self::random_over(rng, config, &[])
+    }
+
+    /// a layer with a color that goes on top of the `under` colors
+    pub fn random_over(rng: &mut Rng, config: &RandomConfig, under: &[Rgb<u8>]) -> Self
+    {
+        let mut layer = Self::new(config.color(rng, under), config.shapes.random(rng));
+        layer.recolor_fimbriation(rng, config, under);
+
+        layer
+    }
+
+    /// after the shape changed, gives its fimbriation the color of the old shape's `fimbriation`,
+    /// or a new color if the old shape didnt have one
+    pub fn keep_fimbriation(
+        &mut self,
+        rng: &mut Rng,
+        config: &RandomConfig,
+        under: &[Rgb<u8>],
+        fimbriation: Option<Fimbriation>
+    )
+    {
+        match fimbriation
+        {
+            Some(fimbriation) => self.shape.set_fimbriation_color(fimbriation.color),
+            None => self.recolor_fimbriation(rng, config, under)
+        }
+    }
+
+    /// picks the fimbriation's color, if the shape has one, to go between the layer and the `under` colors
+    pub fn recolor_fimbriation(&mut self, rng: &mut Rng, config: &RandomConfig, under: &[Rgb<u8>])
+    {
+        if self.shape.fimbriation().is_some()
+        {
+            let next_to: Vec<_> = under.iter().copied().chain([self.color]).collect();
+
+            self.shape.set_fimbriation_color(config.color(rng, &next_to));
+        }
+    }
+
+    /// a smaller shape meant to go on top of other layers
+    pub fn random_emblem(rng: &mut Rng, config: &RandomConfig, under: &[Rgb<u8>]) -> Self
+    {
+        let positions = [
+            Vector2::zeros(),
+            Vector2::new(-0.25, -0.25),
+            Vector2::new(-0.3, 0.0)
+        ];
+
+        let position = positions[rng.usize(0..positions.len())];
+
+        Self::random_over(rng, config, under)
+            .with_position(position)
+            .with_scale(rng.f32() * 0.25 + 0.25)
+    }
+
+    /// signed distance in pixels from the layer's edge, negative inside
+    pub fn distance(&self, pos: Vector2<f32>, size: Vector2<f32>) -> f32
+    {
+        self.grown_distance(pos, size, 0.0)
+    }
+
+    /// distance to the shape grown by `grow` pixels of the unscaled layer
+    fn grown_distance(&self, pos: Vector2<f32>, size: Vector2<f32>, grow: f32) -> f32
+    {
+        let center = size / 2.0;
+        let local = center + (pos - center - self.position.component_mul(&size)) / self.scale;
+
+        // the shape is clipped to its scaled copy of the flag
+        let outside = (local - center).abs() - center;
+        let bounds = outside.sup(&Vector2::zeros()).magnitude() + outside.max().min(0.0);
+
+        (self.shape.distance(local, size) - grow).max(bounds) * self.scale
+    }
+
+    /// `f` returns the signed distance in pixels from the shape's edge, negative inside
+    fn draw_with_fn(
+        image: &mut RgbImage,
+        color: Rgb<u8>,
+        mut f: impl FnMut(Vector2<f32>) -> f32
+    )
+    {
+        image.enumerate_pixels_mut().for_each(|(x, y, pixel)|
+        {
+            let pos = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+
+            let coverage = (0.5 - f(pos)).clamp(0.0, 1.0);
+
+            if coverage > 0.0
+            {
+                *pixel = blend(*pixel, color, coverage);
+            };
+        })
+    }
+
+    pub fn draw_on(&self, image: &mut RgbImage)
+    {
+        let size = Vector2::new(image.width() as f32, image.height() as f32);
+
+        let lower_size = size.x.min(size.y);
+
+        let fimbriation = self.shape.fimbriation();
+        let fimbriation_width = fimbriation.map(|x| x.width).unwrap_or(0.0);
+
+        if let Some(outline) = self.outline
+        {
+            let grow = (fimbriation_width + outline.width) * lower_size;
+
+            Self::draw_with_fn(image, outline.color, |pos| self.grown_distance(pos, size, grow));
+        }
+
+        if let Some(fimbriation) = fimbriation
+        {
+            let grow = fimbriation_width * lower_size;
+
+            Self::draw_with_fn(image, fimbriation.color, |pos| self.grown_distance(pos, size, grow));
+        }
+
+        Self::draw_with_fn(image, self.color, |pos| self.distance(pos, size));
+    }
+}
+
+/// a rectangle in the upper hoist corner with its own background and emblem
+#[derive(Debug, Clone)]
+pub struct Canton
+{
+    /// fraction of the flag's width
+    pub width: f32,
+    /// fraction of the flag's height
+    pub height: f32,
+    pub background: FlagBackground,
+    /// drawn in the canton's own space, as if the canton was a whole flag
+    pub emblem: Option<FlagForeground>
+}
+
+impl Canton
+{
+    pub fn new(width: f32, height: f32, background: FlagBackground) -> Self
+    {
+        Self{width, height, background, emblem: None}
+    }
+
+    pub fn with_emblem(mut self, emblem: FlagForeground) -> Self
+    {
+        self.emblem = Some(emblem);
+
+        self
+    }
+
+    /// a canton lined up with the stripes of `field` if they're rows
+    pub fn random_with(rng: &mut Rng, config: &RandomConfig, field: &FlagBackground) -> Self
+    {
+        let height = Self::aligned_height(field);
+
+        let background = if rng.usize(0..4) == 0
+        {
+            FlagBackground::random_stripes(rng, config)
+        } else
+        {
+            FlagBackground::solid(config.color(rng, &field.lines))
+        };
+
+        let emblem = (rng.usize(0..4) != 0).then(||
+        {
+            FlagForeground::random_over(rng, config, &background.lines)
+                .outline_clashing(&background.lines, config.outline_contrast)
+        });
+
+        Self{
+            width: rng.f32() * 0.1 + 0.4,
+            height,
+            background,
+            emblem
+        }
+    }
+
+    /// height ending on the stripe past the middle if `field` is rows, half the flag otherwise
+    pub fn aligned_height(field: &FlagBackground) -> f32
+    {
+        let amount = field.lines.len();
+
+        let rows = field.stripes().filter(|(horizontal, _)| !horizontal);
+
+        if let (Some((_, ends)), true) = (rows, amount > 1)
+        {
+            ends[amount.div_ceil(2) - 1]
+        } else
+        {
+            0.5
+        }
+    }
+
+    /// size of the canton in pixels on a `width` by `height` flag
+    pub fn pixel_size(&self, width: u32, height: u32) -> (u32, u32)
+    {
+        let size = |fraction: f32, length: u32|
+        {
+            ((fraction.clamp(0.0, 1.0) * length as f32).round() as u32).max(1)
+        };
+
+        (size(self.width, width), size(self.height, height))
+    }
+}
+
+pub(crate) fn draw_field(
+    background: &FlagBackground,
+    layers: &[FlagForeground],
+    width: u32,
+    height: u32
+) -> RgbImage
+{
+    let size = Vector2::new(width as f32, height as f32);
+
+    let mut image = if let Some((horizontal, ends)) = background.stripes()
+    {
+        RgbImage::from_fn(width, height, |x, y|
+        {
+            let (pos, length) = if horizontal
+            {
+                (x, width)
+            } else
+            {
+                (y, height)
+            };
+
+            // the span of this pixel as fractions of the flag
+            let start = pos as f32 / length as f32;
+            let end = (pos + 1) as f32 / length as f32;
+
+            let index = ends.iter().position(|x| start < *x).unwrap_or(ends.len() - 1);
+            let boundary = ends[index];
+
+            let color = background.color(index);
+            if end <= boundary || index + 1 == ends.len()
+            {
+                color
+            } else
+            {
+                blend(color, background.color(index + 1), (end - boundary) / (end - start))
+            }
+        })
+    } else
+    {
+        let mut image = RgbImage::from_pixel(width, height, background.color(0));
+
+        background.division.regions(background.lines.len(), size).into_iter().for_each(|region|
+        {
+            FlagForeground::draw_with_fn(&mut image, background.color(region.color), |pos|
+            {
+                polygon_distance(pos, &region.points)
+            });
+        });
+
+        image
+    };
+
+    layers.iter().for_each(|layer| layer.draw_on(&mut image));
+
+    image
+}
+
+pub fn create_flag(
+    background: &FlagBackground,
+    layers: &[FlagForeground],
+    canton: Option<&Canton>,
+    width: u32,
+    height: u32
+) -> RgbImage
+{
+    eprintln!("creating {width}x{height} image with {background:?} and {layers:?} and {canton:?}");
+
+    let mut image = draw_field(background, layers, width, height);
+
+    if let Some(canton) = canton
+    {
+        let (canton_width, canton_height) = canton.pixel_size(width, height);
+
+        let emblem = canton.emblem.as_slice();
+        let canton = draw_field(&canton.background, emblem, canton_width, canton_height);
+
+        image::imageops::replace(&mut image, &canton, 0, 0);
+    }
+
+    image
+}
+
+pub fn random_flag(rng: &mut Rng, width: u32, height: u32) -> RgbImage
+{
+    random_flag_with(rng, &RandomConfig::default(), width, height)
+}
+
+/// a flag from `FlagSpec::random_checked`, rerolled until it passes the contrast checks of `config`
+pub fn random_flag_with(rng: &mut Rng, config: &RandomConfig, width: u32, height: u32) -> RgbImage
+{
+    FlagSpec::random_checked(rng, config).render(width, height)
+}
+
+pub fn random_flag_seeded(seed: u64, width: u32, height: u32) -> RgbImage
+{
+    FlagSpec::seeded(seed).render(width, height)
+}
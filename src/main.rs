@@ -1,308 +1,139 @@
+#![allow(clippy::suspicious_else_formatting)]
+
+use std::process::ExitCode;
+
 use sdl2::{
-    pixels::{Color, PixelFormatEnum},
-    render::{WindowCanvas, TextureAccess, Texture, TextureCreator},
-    video::WindowContext,
     event::{WindowEvent, Event},
-    keyboard::Keycode
+    keyboard::Keycode,
+    mouse::{MouseButton, MouseWheelDirection}
 };
 
-use strum::EnumCount;
+use flaggen::FlagSpec;
 
-use nalgebra::Vector2;
+use viewer::Viewer;
 
-use image::{RgbImage, Rgb};
+mod cli;
+mod viewer;
 
 
-#[derive(Debug)]
-pub struct FlagBackground
+fn run_viewer(options: cli::ViewerOptions) -> Result<(), cli::CliError>
 {
-    horizontal: bool,
-    lines: Vec<Rgb<u8>>
-}
+    let mut specs: Vec<_> = options.spec.as_ref().map(FlagSpec::load).transpose()?.into_iter().collect();
 
-pub fn random_color() -> Rgb<u8>
-{
-    let r = ||
+    if let Some(browse) = &options.browse
     {
-        fastrand::u8(0..=u8::MAX)
-    };
-
-    Rgb([r(), r(), r()])
-}
+        specs.extend(cli::load_favorites(browse)?);
+    }
 
-impl FlagBackground
-{
-    pub fn random() -> Self
-    {
-        let amount = fastrand::usize(1..6);
-        let lines = (0..amount).map(|_| random_color()).collect();
+    let ctx = sdl2::init().unwrap();
 
-        FlagBackground{
-            horizontal: fastrand::bool(),
-            lines
-        }
-    }
-}
+    let video = ctx.video().unwrap();
 
-#[derive(Debug, EnumCount)]
-pub enum FlagForegroundShape
-{
-    Circle,
-    Ring(f32),
-    LeftTriangle,
-    Cross{thickness: f32},
-    Plus{ratio: f32, thickness: f32}
-}
+    let (width, height) = options.flag_size();
+    let window = video.window("flag generator!", width, height)
+        .resizable()
+        .build()
+        .unwrap();
 
-impl FlagForegroundShape
-{
-    pub fn random() -> Self
-    {
-        match fastrand::usize(0..Self::COUNT)
-        {
-            0 => Self::Circle,
-            1 => Self::Ring(fastrand::f32() * 0.5 + 0.1),
-            2 => Self::LeftTriangle,
-            3 => Self::Cross{thickness: fastrand::f32() * 0.2 + 0.05},
-            4 => Self::Plus{ratio: fastrand::f32() * 3.0 + 2.0, thickness: fastrand::f32() * 0.2 + 0.05},
-            _ => unreachable!()
-        }
-    }
-}
+    let canvas = window.into_canvas().build().unwrap();
+    let creator = canvas.texture_creator();
 
-#[derive(Debug)]
-pub struct FlagForeground
-{
-    color: Rgb<u8>,
-    shape: FlagForegroundShape
-}
+    let mut events = ctx.event_pump().unwrap();
 
-impl FlagForeground
-{
-    pub fn random() -> Self
+    if specs.is_empty()
     {
-        Self{
-            color: random_color(),
-            shape: FlagForegroundShape::random()
-        }
+        specs.push(FlagSpec::seeded_with(fastrand::u64(..), &options.shared.config));
     }
 
-    fn draw_with_fn(
-        image: &mut RgbImage,
-        color: Rgb<u8>,
-        mut f: impl FnMut(Vector2<i32>) -> bool
-    )
-    {
-        image.enumerate_pixels_mut().for_each(|(x, y, pixel)|
-        {
-            let pos = Vector2::new(x as i32, y as i32);
-
-            if f(pos)
-            {
-                *pixel = color;
-            };
-        })
-    }
+    let mut viewer = Viewer::new(canvas, &creator, options, specs);
 
-    pub fn draw_on(&self, image: &mut RgbImage)
+    for event in events.wait_iter()
     {
-        let size: Vector2<i32> = Vector2::new(image.width(), image.height()).cast();
-        let lower_size = Vector2::repeat(image.width().min(image.height()) as f32);
-
-        let aspect = size.x as f32 / size.y as f32;
-
-        match self.shape
+        match event
         {
-            FlagForegroundShape::Circle
-            | FlagForegroundShape::Ring(_) =>
+            Event::Quit{..} => return Ok(()),
+            Event::KeyDown{keycode: Some(Keycode::Space), ..} =>
             {
-                let radius = 0.8 / 2.0;
-
-                Self::draw_with_fn(image, self.color, |pos|
-                {
-                    let pos = (pos - size / 2).map(|x| x as f32).component_div(&lower_size);
-
-                    let distance = pos.magnitude();
-
-                    match self.shape
-                    {
-                        FlagForegroundShape::Circle =>
-                        {
-                            distance <= radius
-                        },
-                        FlagForegroundShape::Ring(ring_width) =>
-                        {
-                            ((radius - ring_width / 2.0)..=radius).contains(&distance)
-                        },
-                        _ => unreachable!()
-                    }
-                });
+                let spec = FlagSpec::seeded_with(fastrand::u64(..), &viewer.options().shared.config);
+                viewer.show(spec);
             },
-            FlagForegroundShape::LeftTriangle =>
+            Event::KeyDown{keycode: Some(Keycode::B), ..} => viewer.reroll(FlagSpec::reroll_background),
+            Event::KeyDown{keycode: Some(Keycode::N), ..} => viewer.reroll(FlagSpec::reroll_shapes),
+            Event::KeyDown{keycode: Some(Keycode::K), ..} => viewer.reroll(FlagSpec::reroll_colors),
+            Event::KeyDown{keycode: Some(Keycode::P), ..} => viewer.reroll(FlagSpec::reroll_parameters),
+            Event::KeyDown{keycode: Some(Keycode::E), ..} => viewer.toggle_editing(),
+            Event::KeyDown{keycode: Some(Keycode::Up), ..} if viewer.is_editing() => viewer.select(-1),
+            Event::KeyDown{keycode: Some(Keycode::Down), ..} if viewer.is_editing() => viewer.select(1),
+            Event::KeyDown{keycode: Some(Keycode::Left), ..} if viewer.is_editing() => viewer.adjust(-1),
+            Event::KeyDown{keycode: Some(Keycode::Right), ..} if viewer.is_editing() => viewer.adjust(1),
+            Event::MouseWheel{y, direction, ..} if viewer.is_editing() && y != 0 =>
             {
-                Self::draw_with_fn(image, self.color, |pos|
-                {
-                    let pos = pos.map(|x| x as f32).component_div(&lower_size);
+                let steps = if direction == MouseWheelDirection::Flipped { -y } else { y };
 
-                    (pos.x + (pos.y - 0.5).abs()) < 0.5
-                });
+                viewer.adjust(steps);
+            },
+            Event::MouseButtonDown{mouse_btn: MouseButton::Left, x, y, ..} if viewer.is_editing() =>
+            {
+                viewer.recolor_at(x, y);
             },
-            FlagForegroundShape::Cross{thickness} =>
+            Event::KeyDown{keycode: Some(Keycode::Left), ..} => viewer.back(),
+            Event::KeyDown{keycode: Some(Keycode::Right), ..} => viewer.forward(),
+            Event::KeyDown{keycode: Some(Keycode::S), ..} =>
             {
-                Self::draw_with_fn(image, self.color, |pos|
+                match viewer.save()
                 {
-                    let pos = pos.map(|x| x as f32).component_div(&size.cast()) - Vector2::repeat(0.5);
-
-                    (pos.x.abs() - pos.y.abs()).abs() < thickness
-                });
+                    Ok(path) => eprintln!("saved {}", path.display()),
+                    Err(err) => eprintln!("error saving flag: {err}")
+                }
             },
-            FlagForegroundShape::Plus{ratio, thickness} =>
+            Event::KeyDown{keycode: Some(Keycode::F), ..} =>
             {
-                Self::draw_with_fn(image, self.color, |pos|
+                match viewer.add_favorite()
                 {
-                    let pos = pos.map(|x| x as f32).component_div(&lower_size);
-
-                    let inside_vertical = (pos.x - aspect / ratio).abs() < thickness;
-                    let inside_horizontal = (pos.y - 0.5).abs() < thickness;
-
-                    inside_vertical || inside_horizontal
-                });
-            }
+                    Ok(path) => eprintln!("added to {}", path.display()),
+                    Err(err) => eprintln!("error adding favorite: {err}")
+                }
+            },
+            Event::KeyDown{keycode: Some(Keycode::V), ..} => viewer.cycle_vision(),
+            Event::Window{win_event: WindowEvent::SizeChanged(..), ..} => viewer.resized(),
+            Event::Window{win_event: WindowEvent::Exposed, ..} => viewer.present(),
+            _ => ()
         }
     }
+
+    Ok(())
 }
 
-pub fn create_flag(
-    background: FlagBackground,
-    foreground: Option<FlagForeground>,
-    width: u32,
-    height: u32
-) -> RgbImage
+fn main() -> ExitCode
 {
-    eprintln!("creating {width}x{height} image with {background:?} and {foreground:?}");
+    let mut args = std::env::args().skip(1);
 
-    let mut background = RgbImage::from_fn(width, height, |x, y|
+    let result = match args.next().as_deref()
     {
-        let pos = if background.horizontal
+        Some("generate") =>
         {
-            x as f32 / width as f32
-        } else
+            cli::GenerateOptions::parse(args).and_then(|options| cli::generate(&options))
+        },
+        Some("-h" | "--help") =>
         {
-            y as f32 / height as f32
-        };
-
-        let pos = pos * background.lines.len() as f32;
-
-        background.lines[pos as usize]
-    });
-
-    if let Some(foreground) = foreground
-    {
-        foreground.draw_on(&mut background);
-    }
-
-    background
-}
-
-pub fn random_flag(width: u32, height: u32) -> RgbImage
-{
-    let background = FlagBackground::random();
-
-    let mut has_foreground = fastrand::bool();
-
-    let solid = background.lines.len() == 1;
-    if solid
-    {
-        has_foreground = true;
-    }
-
-    let mut foreground = has_foreground.then(FlagForeground::random);
-
-    if let (Some(foreground), true) = (foreground.as_mut(), solid)
-    {
-        foreground.shape = FlagForegroundShape::Circle;
-    }
-
-    create_flag(
-        background,
-        foreground,
-        width,
-        height
-    )
-}
-
-fn main()
-{
-    let ctx = sdl2::init().unwrap();
-
-    let video = ctx.video().unwrap();
-
-    let window = video.window("flag generator!", 640, 360)
-        .resizable()
-        .build()
-        .unwrap();
-
-    let mut canvas = window.into_canvas().build().unwrap();
-    let creator = canvas.texture_creator();
-
-    let mut events = ctx.event_pump().unwrap();
-
-    let mut texture = None;
-
-    fn next_flag<'a>(
-        canvas: &mut WindowCanvas,
-        creator: &'a TextureCreator<WindowContext>,
-        texture: &mut Option<Texture<'a>>
-    )
-    {
-        let (width, height) = canvas.window().size();
-        let flag = random_flag(width, height);
-
-        *texture = Some(creator.create_texture(
-            PixelFormatEnum::RGB24,
-            TextureAccess::Static,
-            width,
-            height
-        ).unwrap());
-
-        texture.as_mut().unwrap().update(None, &flag, flag.width() as usize * 3).unwrap();
-
-        canvas.copy(texture.as_ref().unwrap(), None, None).unwrap();
-
-        canvas.present();
-
-        let path = "flag.png";
-        flag.save(path).unwrap();
-    }
+            println!("{}", cli::USAGE);
 
-    next_flag(&mut canvas, &creator, &mut texture);
+            Ok(())
+        },
+        first =>
+        {
+            cli::ViewerOptions::parse(first.map(str::to_owned).into_iter().chain(args)).and_then(run_viewer)
+        }
+    };
 
-    for event in events.wait_iter()
+    match result
     {
-        match event
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) =>
         {
-            Event::Quit{..} => return,
-            Event::KeyDown{keycode: Some(Keycode::Space), ..} =>
-            {
-                next_flag(&mut canvas, &creator, &mut texture);
-            },
-            Event::Window{win_event, ..} =>
-            {
-                match win_event
-                {
-                    WindowEvent::Exposed =>
-                    {
-                        canvas.set_draw_color(Color::RGB(0, 0, 0));
-                        canvas.clear();
-
-                        canvas.copy(texture.as_ref().unwrap(), None, None).unwrap();
+            eprintln!("flaggen: {err}");
 
-                        canvas.present();
-                    },
-                    _ => ()
-                }
-            },
-            _ => ()
+            ExitCode::from(err.exit_code())
         }
     }
 }
@@ -0,0 +1,121 @@
+use nalgebra::Vector2;
+
+use fastrand::Rng;
+
+use crate::{
+    random_color,
+    json::Value,
+    spec::{self, SpecValue, SpecError},
+    shape::Fimbriation,
+    svg
+};
+
+
+/// an off center cross like on the scandinavian flags
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NordicCross
+{
+    /// center of the vertical arm as a fraction of the flag's width
+    pub hoist: f32,
+    /// thickness of the vertical arm as a fraction of the flag's shorter side
+    pub vertical: f32,
+    /// thickness of the horizontal arm as a fraction of the flag's shorter side
+    pub horizontal: f32,
+    /// border of a second color around the cross
+    pub fimbriation: Option<Fimbriation>
+}
+
+impl NordicCross
+{
+    /// proportions of the danish flag
+    pub const DENMARK: Self = Self{
+        hoist: 14.0 / 37.0,
+        vertical: 4.0 / 28.0,
+        horizontal: 4.0 / 28.0,
+        fimbriation: None
+    };
+
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        let thickness = rng.f32() * 0.08 + 0.1;
+
+        let horizontal = if rng.usize(0..4) == 0
+        {
+            thickness * (rng.f32() * 0.4 + 0.8)
+        } else
+        {
+            thickness
+        };
+
+        // random layers pick the fimbriation's color again with their config, see `FlagForeground::random_over`
+        let fimbriation = (rng.usize(0..5) < 2).then(||
+        {
+            Fimbriation{color: random_color(rng), width: thickness * (rng.f32() * 0.2 + 0.3)}
+        });
+
+        Self{
+            hoist: rng.f32() * 0.07 + 0.33,
+            vertical: thickness,
+            horizontal,
+            fimbriation
+        }
+    }
+
+    pub fn distance(&self, pos: Vector2<f32>, size: Vector2<f32>) -> f32
+    {
+        let lower_size = size.x.min(size.y);
+
+        let vertical = (pos.x - self.hoist * size.x).abs() - self.vertical * lower_size / 2.0;
+        let horizontal = (pos.y - size.y / 2.0).abs() - self.horizontal * lower_size / 2.0;
+
+        vertical.min(horizontal)
+    }
+
+    pub fn svg(&self, output: &mut String, color: &str, size: Vector2<f32>)
+    {
+        let lower_size = size.x.min(size.y);
+
+        let vertical = self.vertical * lower_size;
+        svg::rect(output, color, Vector2::new(self.hoist * size.x - vertical / 2.0, 0.0), Vector2::new(vertical, size.y));
+
+        let horizontal = self.horizontal * lower_size;
+        svg::rect(output, color, Vector2::new(0.0, (size.y - horizontal) / 2.0), Vector2::new(size.x, horizontal));
+    }
+
+    pub fn description(&self) -> String
+    {
+        if self.fimbriation.is_some()
+        {
+            "fimbriated nordic cross".to_owned()
+        } else
+        {
+            "nordic cross".to_owned()
+        }
+    }
+
+    pub fn parameters(&self) -> Vec<(&'static str, Value)>
+    {
+        vec![
+            ("hoist", self.hoist.into()),
+            ("vertical", self.vertical.into()),
+            ("horizontal", self.horizontal.into()),
+            ("fimbriation", self.fimbriation.as_ref().map(SpecValue::to_value).unwrap_or(Value::Null))
+        ]
+    }
+
+    pub fn from_parameters(value: &Value) -> Result<Self, SpecError>
+    {
+        let fimbriation = match value.get("fimbriation")
+        {
+            None | Some(Value::Null) => None,
+            Some(x) => Some(Fimbriation::from_value(x)?)
+        };
+
+        Ok(Self{
+            hoist: spec::f32_in(value, "hoist", 0.0..=1.0)?,
+            vertical: spec::f32_in(value, "vertical", 0.005..=1.0)?,
+            horizontal: spec::f32_in(value, "horizontal", 0.005..=1.0)?,
+            fimbriation
+        })
+    }
+}
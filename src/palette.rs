@@ -0,0 +1,213 @@
+use image::Rgb;
+
+use fastrand::Rng;
+
+use crate::color_distance;
+
+
+/// a named color of a palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tincture
+{
+    pub name: String,
+    pub color: Rgb<u8>,
+    /// metals are the light tinctures, the rule of tincture keeps metals off metals and colours off colours
+    pub metal: bool
+}
+
+impl Tincture
+{
+    pub fn new(name: impl Into<String>, color: Rgb<u8>, metal: bool) -> Self
+    {
+        Self{name: name.into(), color, metal}
+    }
+
+    pub fn metal(name: impl Into<String>, color: Rgb<u8>) -> Self
+    {
+        Self::new(name, color, true)
+    }
+
+    pub fn colour(name: impl Into<String>, color: Rgb<u8>) -> Self
+    {
+        Self::new(name, color, false)
+    }
+}
+
+/// a limited set of colors random flags pick from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette
+{
+    pub name: String,
+    /// must not be empty
+    pub tinctures: Vec<Tincture>
+}
+
+impl Palette
+{
+    /// names of the palettes `builtin` knows about
+    pub const BUILTIN: [&'static str; 3] = ["heraldic", "modern", "muted"];
+
+    pub fn new(name: impl Into<String>, tinctures: Vec<Tincture>) -> Self
+    {
+        assert!(!tinctures.is_empty(), "palette must have at least one tincture");
+
+        Self{name: name.into(), tinctures}
+    }
+
+    pub fn builtin(name: &str) -> Option<Self>
+    {
+        match name
+        {
+            "heraldic" => Some(Self::heraldic()),
+            "modern" => Some(Self::modern()),
+            "muted" => Some(Self::muted()),
+            _ => None
+        }
+    }
+
+    /// the tinctures of heraldry
+    pub fn heraldic() -> Self
+    {
+        Self::new("heraldic", vec![
+            Tincture::metal("or", Rgb([252, 209, 22])),
+            Tincture::metal("argent", Rgb([255, 255, 255])),
+            Tincture::colour("gules", Rgb([200, 16, 46])),
+            Tincture::colour("azure", Rgb([0, 56, 168])),
+            Tincture::colour("vert", Rgb([0, 122, 61])),
+            Tincture::colour("sable", Rgb([0, 0, 0])),
+            Tincture::colour("purpure", Rgb([117, 38, 132]))
+        ])
+    }
+
+    /// colors common on current national flags
+    pub fn modern() -> Self
+    {
+        Self::new("modern", vec![
+            Tincture::metal("white", Rgb([255, 255, 255])),
+            Tincture::metal("yellow", Rgb([255, 205, 0])),
+            Tincture::metal("sky blue", Rgb([117, 170, 219])),
+            Tincture::colour("red", Rgb([218, 41, 28])),
+            Tincture::colour("maroon", Rgb([134, 38, 51])),
+            Tincture::colour("orange", Rgb([255, 130, 0])),
+            Tincture::colour("green", Rgb([0, 150, 57])),
+            Tincture::colour("blue", Rgb([0, 82, 180])),
+            Tincture::colour("navy", Rgb([0, 36, 125])),
+            Tincture::colour("black", Rgb([0, 0, 0]))
+        ])
+    }
+
+    /// faded, less saturated colors
+    pub fn muted() -> Self
+    {
+        Self::new("muted", vec![
+            Tincture::metal("cream", Rgb([238, 228, 200])),
+            Tincture::metal("sand", Rgb([214, 186, 124])),
+            Tincture::colour("brick", Rgb([156, 68, 56])),
+            Tincture::colour("slate", Rgb([72, 94, 120])),
+            Tincture::colour("olive", Rgb([96, 110, 60])),
+            Tincture::colour("charcoal", Rgb([52, 52, 56])),
+            Tincture::colour("plum", Rgb([106, 70, 96]))
+        ])
+    }
+
+    pub fn find(&self, color: Rgb<u8>) -> Option<&Tincture>
+    {
+        self.tinctures.iter().find(|tincture| tincture.color == color)
+    }
+
+    /// picks a color at least `min_distance` away from every color in `next_to`, if `tincture` is set
+    /// it also follows the rule of tincture when all of `next_to` are metals or all are colours
+    pub fn random(&self, rng: &mut Rng, next_to: &[Rgb<u8>], tincture: bool, min_distance: f32) -> Rgb<u8>
+    {
+        let metals: Vec<_> = next_to.iter().filter_map(|color| self.find(*color).map(|x| x.metal)).collect();
+
+        let forbidden_kind = (tincture && metals.len() == next_to.len())
+            .then(|| metals.first().copied().filter(|first| metals.iter().all(|x| x == first)))
+            .flatten();
+
+        let distinct = |tincture: &&Tincture|
+        {
+            next_to.iter().all(|color| color_distance(tincture.color, *color) >= min_distance)
+        };
+
+        let allowed: Vec<_> = self.tinctures.iter()
+            .filter(|tincture| !next_to.contains(&tincture.color))
+            .filter(|tincture| Some(tincture.metal) != forbidden_kind)
+            .collect();
+
+        // the rule of tincture matters more than the distance if both cant be kept
+        let distant: Vec<_> = allowed.iter().copied().filter(distinct).collect();
+
+        let candidates = if !distant.is_empty()
+        {
+            distant
+        } else if !allowed.is_empty()
+        {
+            allowed
+        } else
+        {
+            self.tinctures.iter().collect()
+        };
+
+        candidates[rng.usize(0..candidates.len())].color
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    fn picks(palette: &Palette, next_to: &[Rgb<u8>], min_distance: f32) -> Vec<Rgb<u8>>
+    {
+        let mut rng = Rng::with_seed(0);
+
+        (0..200).map(|_| palette.random(&mut rng, next_to, true, min_distance)).collect()
+    }
+
+    #[test]
+    fn follows_rule_of_tincture()
+    {
+        let palette = Palette::heraldic();
+        let metal = |color| palette.find(color).unwrap().metal;
+
+        let or = Rgb([252, 209, 22]);
+        let gules = Rgb([200, 16, 46]);
+
+        assert!(picks(&palette, &[or], 0.0).into_iter().all(|color| !metal(color)));
+        assert!(picks(&palette, &[gules], 0.0).into_iter().all(metal));
+
+        // mixed neighbours dont forbid either kind
+        let mixed = picks(&palette, &[or, gules], 0.0);
+        assert!(mixed.iter().any(|color| metal(*color)) && mixed.iter().any(|color| !metal(*color)));
+    }
+
+    #[test]
+    fn falls_back_in_order()
+    {
+        let white = Rgb([255, 255, 255]);
+        let ivory = Rgb([250, 245, 230]);
+        let black = Rgb([0, 0, 0]);
+        let grey = Rgb([40, 40, 40]);
+
+        let palette = Palette::new("test", vec![
+            Tincture::metal("white", white),
+            Tincture::colour("black", black),
+            Tincture::colour("grey", grey)
+        ]);
+
+        // only black is far enough from white
+        let distant = picks(&palette, &[white], color_distance(white, black));
+        assert!(distant.into_iter().all(|color| color == black));
+
+        // nothing is far enough so any allowed colour goes
+        let allowed = picks(&palette, &[white], f32::MAX);
+        assert!(allowed.contains(&black) && allowed.contains(&grey) && !allowed.contains(&white));
+
+        // every other tincture breaks the rule so the whole palette goes
+        let metals = Palette::new("metals", vec![Tincture::metal("white", white), Tincture::metal("ivory", ivory)]);
+        let any = picks(&metals, &[white], 0.0);
+        assert!(any.contains(&white) && any.contains(&ivory));
+    }
+}
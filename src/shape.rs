@@ -0,0 +1,635 @@
+use std::{
+    fmt::{self, Debug},
+    sync::Arc
+};
+
+use nalgebra::Vector2;
+
+use image::Rgb;
+
+use fastrand::Rng;
+
+use crate::{
+    polygon_distance,
+    step_parameter,
+    json::Value,
+    spec::{self, SpecValue, SpecError},
+    svg,
+    Star,
+    StarArrangement,
+    Crescent,
+    Sun,
+    NordicCross
+};
+
+
+/// a border of a second color around a shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fimbriation
+{
+    pub color: Rgb<u8>,
+    /// as a fraction of the flag's shorter side
+    pub width: f32
+}
+
+
+/// a shape that can be drawn as a flag's foreground
+pub trait FlagShape: Debug + Send + Sync
+{
+    /// name used as the `type` of the shape in specs
+    fn name(&self) -> &str;
+
+    fn description(&self) -> String;
+
+    /// signed distance in pixels from `pos` to the shape's edge, negative inside
+    fn distance(&self, pos: Vector2<f32>, size: Vector2<f32>) -> f32;
+
+    /// writes the svg elements of the shape, in pixel coordinates of a `size` flag
+    fn svg(&self, output: &mut String, color: &str, size: Vector2<f32>);
+
+    /// parameters saved in specs next to the `type`
+    fn parameters(&self) -> Vec<(&'static str, Value)>
+    {
+        Vec::new()
+    }
+
+    /// border drawn underneath the shape
+    fn fimbriation(&self) -> Option<Fimbriation>
+    {
+        None
+    }
+}
+
+/// a shape that can be sampled randomly and loaded back from a spec
+pub trait RegisteredShape: FlagShape + Sized + 'static
+{
+    const NAME: &'static str;
+
+    fn random(rng: &mut Rng) -> Self;
+
+    fn from_parameters(value: &Value) -> Result<Self, SpecError>;
+}
+
+#[derive(Debug, Clone)]
+pub enum FlagForegroundShape
+{
+    Circle,
+    Ring(f32),
+    LeftTriangle,
+    Cross{thickness: f32},
+    Plus{ratio: f32, thickness: f32},
+    Star(Star),
+    /// copies of `star` laid out around its position
+    Stars{star: Star, arrangement: StarArrangement},
+    Crescent(Crescent),
+    /// `star_size` is a fraction of the crescent's radius
+    CrescentStar{crescent: Crescent, points: u32, star_size: f32},
+    Sun(Sun),
+    NordicCross(NordicCross),
+    Custom(Arc<dyn FlagShape>)
+}
+
+impl FlagForegroundShape
+{
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        ShapeRegistry::default().random(rng)
+    }
+
+    pub fn custom(shape: impl FlagShape + 'static) -> Self
+    {
+        Self::Custom(Arc::new(shape))
+    }
+
+    /// changes the color of the fimbriation if there is one, custom shapes keep theirs
+    pub fn set_fimbriation_color(&mut self, color: Rgb<u8>)
+    {
+        if let Self::NordicCross(NordicCross{fimbriation: Some(fimbriation), ..}) = self
+        {
+            fimbriation.color = color;
+        }
+    }
+}
+
+const RADIUS: f32 = 0.8 / 2.0;
+
+impl FlagShape for FlagForegroundShape
+{
+    fn name(&self) -> &str
+    {
+        match self
+        {
+            Self::Circle => "circle",
+            Self::Ring(_) => "ring",
+            Self::LeftTriangle => "left_triangle",
+            Self::Cross{..} => "cross",
+            Self::Plus{..} => "plus",
+            Self::Star(_) => "star",
+            Self::Stars{..} => "stars",
+            Self::Crescent(_) => "crescent",
+            Self::CrescentStar{..} => "crescent_star",
+            Self::Sun(_) => "sun",
+            Self::NordicCross(_) => "nordic_cross",
+            Self::Custom(shape) => shape.name()
+        }
+    }
+
+    fn description(&self) -> String
+    {
+        match self
+        {
+            Self::Circle => "circle".to_owned(),
+            Self::Ring(width) => format!("ring with width {width}"),
+            Self::LeftTriangle => "triangle at the hoist".to_owned(),
+            Self::Cross{thickness} => format!("saltire with thickness {thickness}"),
+            Self::Plus{ratio, thickness} => format!("cross at 1/{ratio} of the width with thickness {thickness}"),
+            Self::Star(star) => star.description(),
+            Self::Stars{star, arrangement} => format!("{} of {}s", arrangement.description(), star.description()),
+            Self::Crescent(crescent) => crescent.description(),
+            Self::CrescentStar{crescent, points, ..} => format!("{} with a {points} pointed star", crescent.description()),
+            Self::Sun(sun) => sun.description(),
+            Self::NordicCross(cross) => cross.description(),
+            Self::Custom(shape) => shape.description()
+        }
+    }
+
+    fn distance(&self, pos: Vector2<f32>, size: Vector2<f32>) -> f32
+    {
+        let lower_size = size.x.min(size.y);
+
+        let aspect = size.x / size.y;
+
+        match self
+        {
+            Self::Circle =>
+            {
+                ((pos - size / 2.0).magnitude() / lower_size - RADIUS) * lower_size
+            },
+            Self::Ring(ring_width) =>
+            {
+                let distance = (pos - size / 2.0).magnitude() / lower_size;
+                let half_width = ring_width / 4.0;
+
+                ((distance - (RADIUS - half_width)).abs() - half_width) * lower_size
+            },
+            Self::LeftTriangle =>
+            {
+                let points = [
+                    Vector2::zeros(),
+                    Vector2::repeat(lower_size / 2.0),
+                    Vector2::new(0.0, lower_size)
+                ];
+
+                polygon_distance(pos, &points)
+            },
+            Self::Cross{thickness} =>
+            {
+                // length of the gradient of x / width - y / height
+                let gradient = size.map(|x| x.recip()).magnitude();
+
+                let pos = pos.component_div(&size) - Vector2::repeat(0.5);
+
+                ((pos.x.abs() - pos.y.abs()).abs() - thickness) / gradient
+            },
+            Self::Plus{ratio, thickness} =>
+            {
+                let pos = pos / lower_size;
+
+                let vertical = (pos.x - aspect / ratio).abs() - thickness;
+                let horizontal = (pos.y - 0.5).abs() - thickness;
+
+                vertical.min(horizontal) * lower_size
+            },
+            Self::Star(star) =>
+            {
+                star.distance_at(pos, size / 2.0 + star.position.component_mul(&size), star.size * lower_size)
+            },
+            Self::Stars{star, arrangement} =>
+            {
+                let center = size / 2.0 + star.position.component_mul(&size);
+                let radius = star.size * lower_size;
+
+                arrangement.offsets().into_iter().fold(f32::MAX, |closest, offset|
+                {
+                    let center = center + offset * lower_size;
+
+                    // cant be closer than the bounding circle
+                    if (pos - center).magnitude() - radius > closest
+                    {
+                        return closest;
+                    }
+
+                    closest.min(star.distance_at(pos, center, radius))
+                })
+            },
+            Self::Crescent(crescent) =>
+            {
+                crescent.distance_at(pos, crescent.center(size), crescent.size * lower_size)
+            },
+            Self::CrescentStar{crescent, points, star_size} =>
+            {
+                let center = crescent.center(size);
+                let radius = crescent.size * lower_size;
+
+                let star_center = crescent.star_center(center, radius, *star_size);
+                let star = crescent.star(*points, *star_size);
+
+                crescent.distance_at(pos, center, radius)
+                    .min(star.distance_at(pos, star_center, star.size * lower_size))
+            },
+            Self::Sun(sun) =>
+            {
+                sun.distance_at(pos, sun.center(size), sun.size * lower_size)
+            },
+            Self::NordicCross(cross) => cross.distance(pos, size),
+            Self::Custom(shape) => shape.distance(pos, size)
+        }
+    }
+
+    fn svg(&self, output: &mut String, color: &str, size: Vector2<f32>)
+    {
+        let lower_size = size.x.min(size.y);
+        let aspect = size.x / size.y;
+
+        let center = size / 2.0;
+
+        match self
+        {
+            Self::Circle =>
+            {
+                svg::circle(output, color, center, RADIUS * lower_size);
+            },
+            Self::Ring(ring_width) =>
+            {
+                let stroke = ring_width / 2.0;
+
+                svg::ring(output, color, center, (RADIUS - stroke / 2.0) * lower_size, stroke * lower_size);
+            },
+            Self::LeftTriangle =>
+            {
+                svg::polygon(output, color, &[
+                    Vector2::zeros(),
+                    Vector2::repeat(lower_size / 2.0),
+                    Vector2::new(0.0, lower_size)
+                ]);
+            },
+            Self::Cross{thickness} =>
+            {
+                let to_pixels = |x: f32, y: f32| Vector2::new(x + 0.5, y + 0.5).component_mul(&size);
+
+                [1.0, -1.0].into_iter().for_each(|direction|
+                {
+                    svg::polygon(output, color, &[
+                        to_pixels(-0.5, -0.5 * direction - thickness),
+                        to_pixels(0.5, 0.5 * direction - thickness),
+                        to_pixels(0.5, 0.5 * direction + thickness),
+                        to_pixels(-0.5, -0.5 * direction + thickness)
+                    ]);
+                });
+            },
+            Self::Plus{ratio, thickness} =>
+            {
+                let vertical = (aspect / ratio - thickness) * lower_size;
+                svg::rect(output, color, Vector2::new(vertical, 0.0), Vector2::new(thickness * 2.0 * lower_size, size.y));
+
+                let horizontal = (0.5 - thickness) * lower_size;
+                svg::rect(output, color, Vector2::new(0.0, horizontal), Vector2::new(size.x, thickness * 2.0 * lower_size));
+            },
+            Self::Star(star) =>
+            {
+                let center = center + star.position.component_mul(&size);
+
+                svg::polygon(output, color, &star.vertices(center, star.size * lower_size));
+            },
+            Self::Stars{star, arrangement} =>
+            {
+                let center = center + star.position.component_mul(&size);
+
+                arrangement.offsets().into_iter().for_each(|offset|
+                {
+                    let vertices = star.vertices(center + offset * lower_size, star.size * lower_size);
+
+                    svg::polygon(output, color, &vertices);
+                });
+            },
+            Self::Crescent(crescent) =>
+            {
+                svg::path(output, color, &crescent.path(crescent.center(size), crescent.size * lower_size));
+            },
+            Self::CrescentStar{crescent, points, star_size} =>
+            {
+                let center = crescent.center(size);
+                let radius = crescent.size * lower_size;
+
+                svg::path(output, color, &crescent.path(center, radius));
+
+                let star = crescent.star(*points, *star_size);
+                let star_center = crescent.star_center(center, radius, *star_size);
+
+                svg::polygon(output, color, &star.vertices(star_center, star.size * lower_size));
+            },
+            Self::Sun(sun) =>
+            {
+                let center = sun.center(size);
+                let radius = sun.size * lower_size;
+
+                svg::circle(output, color, center, radius * sun.ratio);
+
+                sun.rays(center, radius).into_iter().for_each(|ray|
+                {
+                    svg::polygon(output, color, &ray);
+                });
+            },
+            Self::NordicCross(cross) => cross.svg(output, color, size),
+            Self::Custom(shape) => shape.svg(output, color, size)
+        }
+    }
+
+    fn parameters(&self) -> Vec<(&'static str, Value)>
+    {
+        match self
+        {
+            Self::Circle | Self::LeftTriangle => Vec::new(),
+            Self::Ring(width) => vec![("width", (*width).into())],
+            Self::Cross{thickness} => vec![("thickness", (*thickness).into())],
+            Self::Plus{ratio, thickness} => vec![("ratio", (*ratio).into()), ("thickness", (*thickness).into())],
+            Self::Star(star) => star.parameters(),
+            Self::Stars{star, arrangement} =>
+            {
+                let mut parameters = star.parameters();
+                parameters.push(("arrangement", arrangement.to_value()));
+
+                parameters
+            },
+            Self::Crescent(crescent) => crescent.parameters(),
+            Self::CrescentStar{crescent, points, star_size} =>
+            {
+                let mut parameters = crescent.parameters();
+                parameters.push(("points", (*points).into()));
+                parameters.push(("star_size", (*star_size).into()));
+
+                parameters
+            },
+            Self::Sun(sun) => sun.parameters(),
+            Self::NordicCross(cross) => cross.parameters(),
+            Self::Custom(shape) => shape.parameters()
+        }
+    }
+
+    fn fimbriation(&self) -> Option<Fimbriation>
+    {
+        match self
+        {
+            Self::NordicCross(cross) => cross.fimbriation,
+            Self::Custom(shape) => shape.fimbriation(),
+            _ => None
+        }
+    }
+}
+
+type RandomFn = dyn Fn(&mut Rng) -> FlagForegroundShape + Send + Sync;
+type LoadFn = dyn Fn(&Value) -> Result<FlagForegroundShape, SpecError> + Send + Sync;
+
+#[derive(Clone)]
+pub struct ShapeEntry
+{
+    pub name: String,
+    pub weight: f32,
+    random: Arc<RandomFn>,
+    load: Arc<LoadFn>
+}
+
+impl Debug for ShapeEntry
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("ShapeEntry")
+            .field("name", &self.name)
+            .field("weight", &self.weight)
+            .finish()
+    }
+}
+
+/// the shapes random flags pick from, and the shapes specs can be loaded with
+#[derive(Debug, Clone)]
+pub struct ShapeRegistry
+{
+    entries: Vec<ShapeEntry>
+}
+
+impl Default for ShapeRegistry
+{
+    fn default() -> Self
+    {
+        Self::builtin()
+    }
+}
+
+impl ShapeRegistry
+{
+    pub fn empty() -> Self
+    {
+        Self{entries: Vec::new()}
+    }
+
+    pub fn builtin() -> Self
+    {
+        use FlagForegroundShape as Shape;
+
+        let mut this = Self::empty();
+
+        this.register_fn("circle", 1.0, |_| Shape::Circle, |_| Ok(Shape::Circle));
+        this.register_fn(
+            "ring",
+            1.0,
+            |rng| Shape::Ring(rng.f32() * 0.5 + 0.1),
+            |value| Ok(Shape::Ring(spec::f32_in(value, "width", 0.01..=1.0)?))
+        );
+        this.register_fn("left_triangle", 1.0, |_| Shape::LeftTriangle, |_| Ok(Shape::LeftTriangle));
+        this.register_fn(
+            "cross",
+            1.0,
+            |rng| Shape::Cross{thickness: rng.f32() * 0.2 + 0.05},
+            |value| Ok(Shape::Cross{thickness: spec::f32_in(value, "thickness", 0.005..=1.0)?})
+        );
+        this.register_fn(
+            "plus",
+            1.0,
+            |rng| Shape::Plus{ratio: rng.f32() * 3.0 + 2.0, thickness: rng.f32() * 0.2 + 0.05},
+            |value|
+            {
+                Ok(Shape::Plus{
+                    ratio: spec::f32_in(value, "ratio", 1.0..=20.0)?,
+                    thickness: spec::f32_in(value, "thickness", 0.005..=1.0)?
+                })
+            }
+        );
+        this.register_fn(
+            "star",
+            1.0,
+            |rng| Shape::Star(Star::random(rng)),
+            |value| Star::from_parameters(value).map(Shape::Star)
+        );
+        this.register_fn(
+            "stars",
+            1.0,
+            |rng|
+            {
+                let arrangement = StarArrangement::random(rng);
+                let star = Star{size: arrangement.random_star_size(rng), ..Star::random(rng)};
+
+                Shape::Stars{star, arrangement}
+            },
+            |value|
+            {
+                Ok(Shape::Stars{
+                    star: Star::from_parameters(value)?,
+                    arrangement: StarArrangement::from_value(spec::field(value, "arrangement")?)?
+                })
+            }
+        );
+        this.register_fn(
+            "crescent",
+            1.0,
+            |rng| Shape::Crescent(Crescent::random(rng)),
+            |value| Crescent::from_parameters(value).map(Shape::Crescent)
+        );
+        this.register_fn(
+            "crescent_star",
+            1.0,
+            |rng|
+            {
+                Shape::CrescentStar{
+                    crescent: Crescent::random(rng),
+                    points: if rng.usize(0..4) == 0 { rng.u32(6..=8) } else { 5 },
+                    star_size: rng.f32() * 0.15 + 0.3
+                }
+            },
+            |value|
+            {
+                Ok(Shape::CrescentStar{
+                    crescent: Crescent::from_parameters(value)?,
+                    points: spec::u32_in(value, "points", Star::POINTS)?,
+                    star_size: spec::f32_in(value, "star_size", 0.01..=1.0)?
+                })
+            }
+        );
+        this.register_fn(
+            "sun",
+            1.0,
+            |rng| Shape::Sun(Sun::random(rng)),
+            |value| Sun::from_parameters(value).map(Shape::Sun)
+        );
+        this.register_fn(
+            "nordic_cross",
+            1.0,
+            |rng| Shape::NordicCross(NordicCross::random(rng)),
+            |value| NordicCross::from_parameters(value).map(Shape::NordicCross)
+        );
+
+        this
+    }
+
+    /// adds a shape, replacing any shape with the same name
+    pub fn register_fn(
+        &mut self,
+        name: impl Into<String>,
+        weight: f32,
+        random: impl Fn(&mut Rng) -> FlagForegroundShape + Send + Sync + 'static,
+        load: impl Fn(&Value) -> Result<FlagForegroundShape, SpecError> + Send + Sync + 'static
+    )
+    {
+        let name = name.into();
+        self.remove(&name);
+
+        self.entries.push(ShapeEntry{
+            name,
+            weight,
+            random: Arc::new(random),
+            load: Arc::new(load)
+        });
+    }
+
+    pub fn register<T: RegisteredShape>(&mut self, weight: f32)
+    {
+        self.register_fn(
+            T::NAME,
+            weight,
+            |rng| FlagForegroundShape::custom(T::random(rng)),
+            |value| T::from_parameters(value).map(FlagForegroundShape::custom)
+        );
+    }
+
+    pub fn remove(&mut self, name: &str)
+    {
+        self.entries.retain(|entry| entry.name != name);
+    }
+
+    pub fn entries(&self) -> &[ShapeEntry]
+    {
+        &self.entries
+    }
+
+    /// picks a shape with a chance proportional to its weight
+    pub fn random(&self, rng: &mut Rng) -> FlagForegroundShape
+    {
+        let total: f32 = self.entries.iter().map(|entry| entry.weight).sum();
+
+        let mut picked = rng.f32() * total;
+        let entry = self.entries.iter().find(|entry|
+        {
+            picked -= entry.weight;
+
+            picked < 0.0
+        }).or_else(|| self.entries.last()).expect("shape registry must not be empty");
+
+        (entry.random)(rng)
+    }
+
+    /// a shape of the type registered as `name` with new random parameters
+    pub fn random_named(&self, rng: &mut Rng, name: &str) -> Option<FlagForegroundShape>
+    {
+        self.entries.iter().find(|entry| entry.name == name).map(|entry| (entry.random)(rng))
+    }
+
+    /// the same shape with its number parameter `name` moved by `steps` small steps,
+    /// `None` if it has no such parameter, the shape doesnt accept the new value or it didnt change
+    pub fn adjusted(&self, shape: &FlagForegroundShape, name: &str, steps: i32) -> Option<FlagForegroundShape>
+    {
+        let value = shape.to_value();
+        let current = value.get(name)?;
+
+        let with = |x: Value|
+        {
+            let fields = match &value
+            {
+                Value::Object(fields) => fields.iter().map(|(key, value)|
+                {
+                    (key.clone(), if key == name { x.clone() } else { value.clone() })
+                }).collect(),
+                _ => unreachable!("shapes are saved as objects")
+            };
+
+            self.load(&Value::Object(fields)).ok()
+        };
+
+        // whole number parameters like star points dont take fractions, so they move by 1 instead
+        let adjusted = with(step_parameter(current.as_f32()?, steps).into()).or_else(||
+        {
+            with(current.as_u32()?.saturating_add_signed(steps).into())
+        })?;
+
+        // a float sitting on a whole number at its limit can reload as itself
+        (adjusted.to_value().get(name) != Some(current)).then_some(adjusted)
+    }
+
+    pub fn load(&self, value: &Value) -> Result<FlagForegroundShape, SpecError>
+    {
+        let name = spec::str_field(value, "type")?;
+
+        let entry = self.entries.iter().find(|entry| entry.name == name)
+            .ok_or_else(|| SpecError::Invalid{field: "type", value: name.to_owned()})?;
+
+        (entry.load)(value)
+    }
+}
@@ -0,0 +1,889 @@
+use std::{
+    fs,
+    io,
+    fmt::{self, Display},
+    path::Path,
+    ops::RangeInclusive
+};
+
+use nalgebra::Vector2;
+
+use image::{RgbImage, Rgb};
+
+use fastrand::Rng;
+
+use crate::{
+    json::{self, Value},
+    analysis,
+    svg::create_flag_svg,
+    hex_color,
+    create_flag,
+    FlagBackground,
+    Division,
+    RandomConfig,
+    Canton,
+    FlagShape,
+    ShapeRegistry,
+    FlagForeground,
+    FlagForegroundShape,
+    Fimbriation
+};
+
+
+pub const SPEC_VERSION: u32 = 6;
+
+#[derive(Debug)]
+pub enum SpecError
+{
+    Io(io::Error),
+    Parse(json::ParseError),
+    UnsupportedVersion(u32),
+    Missing(&'static str),
+    Invalid{field: &'static str, value: String}
+}
+
+impl Display for SpecError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::Io(x) => write!(f, "{x}"),
+            Self::Parse(x) => write!(f, "parse error: {x}"),
+            Self::UnsupportedVersion(x) =>
+            {
+                write!(f, "spec version {x} is newer than supported version {SPEC_VERSION}")
+            },
+            Self::Missing(field) => write!(f, "missing field `{field}`"),
+            Self::Invalid{field, value} => write!(f, "invalid value for `{field}`: {value}")
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+impl From<io::Error> for SpecError
+{
+    fn from(value: io::Error) -> Self
+    {
+        Self::Io(value)
+    }
+}
+
+impl From<json::ParseError> for SpecError
+{
+    fn from(value: json::ParseError) -> Self
+    {
+        Self::Parse(value)
+    }
+}
+
+pub trait SpecValue: Sized
+{
+    fn to_value(&self) -> Value;
+    fn from_value(value: &Value) -> Result<Self, SpecError>;
+}
+
+pub fn field<'a>(value: &'a Value, name: &'static str) -> Result<&'a Value, SpecError>
+{
+    value.get(name).ok_or(SpecError::Missing(name))
+}
+
+pub fn invalid(field: &'static str, value: &Value) -> SpecError
+{
+    SpecError::Invalid{field, value: value.to_string()}
+}
+
+/// writes `value` to a file, refusing numbers like nan or infinity that couldnt be read back
+pub fn write_value(path: impl AsRef<Path>, value: &Value) -> Result<(), SpecError>
+{
+    if let Some(number) = value.non_finite()
+    {
+        return Err(SpecError::Invalid{field: "number", value: number.to_owned()});
+    }
+
+    fs::write(path, value.to_string() + "\n")?;
+
+    Ok(())
+}
+
+pub fn f32_field(value: &Value, name: &'static str) -> Result<f32, SpecError>
+{
+    let x = field(value, name)?;
+
+    x.as_f32().filter(|x| x.is_finite()).ok_or_else(|| invalid(name, x))
+}
+
+/// a number field that must be inside `range`
+pub fn f32_in(value: &Value, name: &'static str, range: RangeInclusive<f32>) -> Result<f32, SpecError>
+{
+    let x = f32_field(value, name)?;
+
+    if range.contains(&x) { Ok(x) } else { Err(invalid(name, field(value, name)?)) }
+}
+
+pub fn u32_field(value: &Value, name: &'static str) -> Result<u32, SpecError>
+{
+    let x = field(value, name)?;
+
+    x.as_u32().ok_or_else(|| invalid(name, x))
+}
+
+/// a whole number field that must be inside `range`
+pub fn u32_in(value: &Value, name: &'static str, range: RangeInclusive<u32>) -> Result<u32, SpecError>
+{
+    let x = u32_field(value, name)?;
+
+    if range.contains(&x) { Ok(x) } else { Err(invalid(name, field(value, name)?)) }
+}
+
+pub fn bool_field(value: &Value, name: &'static str) -> Result<bool, SpecError>
+{
+    let x = field(value, name)?;
+
+    x.as_bool().ok_or_else(|| invalid(name, x))
+}
+
+pub fn str_field<'a>(value: &'a Value, name: &'static str) -> Result<&'a str, SpecError>
+{
+    let x = field(value, name)?;
+
+    x.as_str().ok_or_else(|| invalid(name, x))
+}
+
+impl SpecValue for Rgb<u8>
+{
+    fn to_value(&self) -> Value
+    {
+        hex_color(*self).into()
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        let text = value.as_str()
+            .and_then(|x| x.strip_prefix('#'))
+            .filter(|x| x.len() == 6 && x.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| invalid("color", value))?;
+
+        let channel = |index: usize|
+        {
+            u8::from_str_radix(&text[index * 2..index * 2 + 2], 16).map_err(|_| invalid("color", value))
+        };
+
+        Ok(Rgb([channel(0)?, channel(1)?, channel(2)?]))
+    }
+}
+
+impl SpecValue for Vector2<f32>
+{
+    fn to_value(&self) -> Value
+    {
+        vec![self.x, self.y].into()
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        let invalid = || invalid("vector", value);
+        let component = |x: &Value| x.as_f32().filter(|x| x.is_finite()).ok_or_else(invalid);
+
+        match value.as_array().ok_or_else(invalid)?
+        {
+            [x, y] => Ok(Vector2::new(component(x)?, component(y)?)),
+            _ => Err(invalid())
+        }
+    }
+}
+
+impl SpecValue for FlagBackground
+{
+    fn to_value(&self) -> Value
+    {
+        Value::object([
+            ("division", self.division.to_value()),
+            ("lines", Value::Array(self.lines.iter().map(SpecValue::to_value).collect())),
+            ("weights", self.weights.clone().map(Value::from).unwrap_or(Value::Null))
+        ])
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        let lines = field(value, "lines")?;
+        let lines = lines.as_array()
+            .filter(|x| !x.is_empty())
+            .ok_or_else(|| invalid("lines", lines))?
+            .iter()
+            .map(Rgb::from_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // older specs only had stripes
+        let division = match value.get("division")
+        {
+            Some(division) => Division::from_value(division)?,
+            None => Division::Stripes{horizontal: bool_field(value, "horizontal")?}
+        };
+
+        let weights = match value.get("weights")
+        {
+            None | Some(Value::Null) => None,
+            Some(weights) =>
+            {
+                let parsed = weights.as_array()
+                    .filter(|x| x.len() == lines.len())
+                    .and_then(|x| x.iter().map(Value::as_f32).collect::<Option<Vec<_>>>())
+                    .filter(|x| x.iter().all(|weight| weight.is_finite() && *weight > 0.0))
+                    .ok_or_else(|| invalid("weights", weights))?;
+
+                Some(parsed)
+            }
+        };
+
+        Ok(Self{division, lines, weights})
+    }
+}
+
+impl SpecValue for Fimbriation
+{
+    fn to_value(&self) -> Value
+    {
+        Value::object([
+            ("color", self.color.to_value()),
+            ("width", self.width.into())
+        ])
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        Ok(Self{
+            color: Rgb::from_value(field(value, "color")?)?,
+            width: f32_in(value, "width", 0.0..=1.0)?
+        })
+    }
+}
+
+impl SpecValue for FlagForegroundShape
+{
+    fn to_value(&self) -> Value
+    {
+        let mut fields = vec![("type", self.name().into())];
+        fields.extend(self.parameters());
+
+        Value::object(fields)
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        ShapeRegistry::default().load(value)
+    }
+}
+
+impl SpecValue for FlagForeground
+{
+    fn to_value(&self) -> Value
+    {
+        Value::object([
+            ("color", self.color.to_value()),
+            ("shape", self.shape.to_value()),
+            ("position", self.position.to_value()),
+            ("scale", self.scale.into()),
+            ("outline", self.outline.as_ref().map(SpecValue::to_value).unwrap_or(Value::Null))
+        ])
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        Self::from_value_with(value, &ShapeRegistry::default())
+    }
+}
+
+impl FlagForeground
+{
+    pub fn from_value_with(value: &Value, shapes: &ShapeRegistry) -> Result<Self, SpecError>
+    {
+        let position = value.get("position").map(Vector2::from_value).transpose()?;
+        let scale = value.get("scale").map(|_| f32_in(value, "scale", FlagForeground::SCALES)).transpose()?;
+
+        let outline = match value.get("outline")
+        {
+            None | Some(Value::Null) => None,
+            Some(x) => Some(Fimbriation::from_value(x)?)
+        };
+
+        Ok(Self{
+            color: Rgb::from_value(field(value, "color")?)?,
+            shape: shapes.load(field(value, "shape")?)?,
+            position: position.unwrap_or_else(Vector2::zeros),
+            scale: scale.unwrap_or(1.0),
+            outline
+        })
+    }
+}
+
+impl SpecValue for Canton
+{
+    fn to_value(&self) -> Value
+    {
+        Value::object([
+            ("width", self.width.into()),
+            ("height", self.height.into()),
+            ("background", self.background.to_value()),
+            ("emblem", self.emblem.as_ref().map(SpecValue::to_value).unwrap_or(Value::Null))
+        ])
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        Self::from_value_with(value, &ShapeRegistry::default())
+    }
+}
+
+impl Canton
+{
+    pub fn from_value_with(value: &Value, shapes: &ShapeRegistry) -> Result<Self, SpecError>
+    {
+        let emblem = match value.get("emblem")
+        {
+            None | Some(Value::Null) => None,
+            Some(x) => Some(FlagForeground::from_value_with(x, shapes)?)
+        };
+
+        Ok(Self{
+            width: f32_field(value, "width")?,
+            height: f32_field(value, "height")?,
+            background: FlagBackground::from_value(field(value, "background")?)?,
+            emblem
+        })
+    }
+}
+
+/// everything needed to render a flag again at any resolution
+#[derive(Debug, Clone)]
+pub struct FlagSpec
+{
+    pub seed: Option<u64>,
+    pub background: FlagBackground,
+    /// drawn in order, so later layers end up on top
+    pub layers: Vec<FlagForeground>,
+    /// drawn over everything else
+    pub canton: Option<Canton>
+}
+
+impl FlagSpec
+{
+    pub fn new(background: FlagBackground) -> Self
+    {
+        Self{seed: None, background, layers: Vec::new(), canton: None}
+    }
+
+    pub fn builder(background: FlagBackground) -> FlagBuilder
+    {
+        FlagBuilder::new(background)
+    }
+
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        Self::random_with(rng, &RandomConfig::default())
+    }
+
+    pub fn random_with(rng: &mut Rng, config: &RandomConfig) -> Self
+    {
+        let config = &config.harmonized(rng);
+
+        let background = FlagBackground::random_with(rng, config);
+
+        let mut amount = match rng.usize(0..20)
+        {
+            0..=9 => 0,
+            10..=16 => 1,
+            17..=18 => 2,
+            _ => 3
+        };
+
+        // cantons only line up with stripes
+        let canton = (background.is_striped() && rng.f32() < config.canton_chance).then(||
+        {
+            Canton::random_with(rng, config, &background)
+        });
+
+        let solid = background.lines.len() == 1;
+        if canton.is_some()
+        {
+            // anything placed in the upper hoist would be hidden by the canton
+            amount = amount.min(1);
+        } else if solid
+        {
+            amount = amount.max(1);
+        }
+
+        // every layer is picked and checked against the stripes and the layers drawn before it
+        let mut under = background.lines.clone();
+        let layers = (0..amount).map(|index|
+        {
+            let mut layer = if index == 0
+            {
+                FlagForeground::random_over(rng, config, &under)
+            } else
+            {
+                FlagForeground::random_emblem(rng, config, &under)
+            };
+
+            if index == 0 && solid
+            {
+                layer.shape = FlagForegroundShape::Circle;
+            }
+
+            let layer = layer.outline_clashing(&under, config.outline_contrast);
+            under.push(layer.edge_color());
+
+            layer
+        }).collect();
+
+        Self{seed: None, background, layers, canton}
+    }
+
+    pub fn seeded(seed: u64) -> Self
+    {
+        Self::seeded_with(seed, &RandomConfig::default())
+    }
+
+    /// rerolls flags failing the contrast checks of `config` a few times before giving up and using the last one
+    pub fn random_checked(rng: &mut Rng, config: &RandomConfig) -> Self
+    {
+        const ATTEMPTS: usize = 16;
+
+        // a small render is plenty to find what covers what
+        let (width, height) = (120, 80);
+
+        let mut spec = Self::random_with(rng, config);
+        for _ in 1..ATTEMPTS
+        {
+            let report = analysis::spec_contrast(&spec, width, height);
+            if report.passes(config.min_layer_contrast, config.min_neighbour_contrast)
+            {
+                break;
+            }
+
+            spec = Self::random_with(rng, config);
+        }
+
+        spec
+    }
+
+    /// a flag from `random_checked` that the same seed always gives back
+    pub fn seeded_with(seed: u64, config: &RandomConfig) -> Self
+    {
+        eprintln!("generating flag with seed {seed}");
+
+        Self{seed: Some(seed), ..Self::random_checked(&mut Rng::with_seed(seed), config)}
+    }
+
+    /// a new background, everything else stays the same
+    pub fn reroll_background(&mut self, rng: &mut Rng, config: &RandomConfig)
+    {
+        let config = &config.harmonized(rng);
+
+        // cantons only line up with stripes
+        self.background = if self.canton.is_some() && config.stripes.is_none()
+        {
+            FlagBackground::random_stripes(rng, config)
+        } else
+        {
+            FlagBackground::random_with(rng, config)
+        };
+
+        if let Some(canton) = &mut self.canton
+        {
+            canton.height = Canton::aligned_height(&self.background);
+        }
+
+        self.seed = None;
+        self.outline_layers(config);
+    }
+
+    /// new shapes for every layer in the same places and colors
+    pub fn reroll_shapes(&mut self, rng: &mut Rng, config: &RandomConfig)
+    {
+        self.layers_over(|layer, under|
+        {
+            let fimbriation = layer.shape.fimbriation();
+            layer.shape = config.shapes.random(rng);
+
+            layer.keep_fimbriation(rng, config, under, fimbriation);
+        });
+
+        self.seed = None;
+        self.outline_layers(config);
+    }
+
+    /// new colors for the background, every layer and their fimbriations, keeping the layout
+    pub fn reroll_colors(&mut self, rng: &mut Rng, config: &RandomConfig)
+    {
+        let config = &config.harmonized(rng);
+
+        let background = &mut self.background;
+        background.lines = FlagBackground::random_lines(rng, config, background.division, background.lines.len());
+
+        if let Some(canton) = &mut self.canton
+        {
+            let background = &mut canton.background;
+
+            background.lines = if background.lines.len() == 1
+            {
+                vec![config.color(rng, &self.background.lines)]
+            } else
+            {
+                FlagBackground::random_lines(rng, config, background.division, background.lines.len())
+            };
+        }
+
+        self.layers_over(|layer, under|
+        {
+            layer.color = config.color(rng, under);
+            layer.recolor_fimbriation(rng, config, under);
+        });
+
+        self.seed = None;
+        self.outline_layers(config);
+    }
+
+    /// new parameters for every layer's shape, like a ring's width, keeping the kinds of shapes and their colors
+    pub fn reroll_parameters(&mut self, rng: &mut Rng, config: &RandomConfig)
+    {
+        self.layers_over(|layer, under|
+        {
+            if let Some(shape) = config.shapes.random_named(rng, layer.shape.name())
+            {
+                let fimbriation = layer.shape.fimbriation();
+                layer.shape = shape;
+
+                layer.keep_fimbriation(rng, config, under, fimbriation);
+            }
+        });
+
+        self.seed = None;
+        self.outline_layers(config);
+    }
+
+    /// gives the background part at `pos` in pixels a new color, the canton's if it's in the canton
+    pub fn recolor_at(&mut self, rng: &mut Rng, config: &RandomConfig, pos: Vector2<f32>, size: Vector2<f32>)
+    {
+        let canton = self.canton.as_mut().and_then(|canton|
+        {
+            let (width, height) = canton.pixel_size(size.x as u32, size.y as u32);
+            let canton_size = Vector2::new(width as f32, height as f32);
+
+            (pos.x < canton_size.x && pos.y < canton_size.y).then_some((canton, canton_size))
+        });
+
+        if let Some((canton, canton_size)) = canton
+        {
+            let index = canton.background.part_at(pos, canton_size);
+            canton.background.recolor(rng, config, index);
+        } else
+        {
+            let index = self.background.part_at(pos, size);
+            self.background.recolor(rng, config, index);
+        }
+
+        self.seed = None;
+        self.outline_layers(config);
+    }
+
+    /// moves a parameter of the `layer`th layer, see `FlagForeground::adjust`
+    pub fn adjust(&mut self, shapes: &ShapeRegistry, layer: usize, name: &str, steps: i32) -> bool
+    {
+        let changed = self.layers_mut().nth(layer).is_some_and(|layer| layer.adjust(shapes, name, steps));
+
+        if changed
+        {
+            self.seed = None;
+        }
+
+        changed
+    }
+
+    /// every layer including the canton's emblem, which comes last
+    pub fn all_layers(&self) -> impl Iterator<Item=&FlagForeground>
+    {
+        self.layers.iter().chain(self.canton.iter().flat_map(|canton| canton.emblem.as_ref()))
+    }
+
+    /// every layer including the canton's emblem, which comes last
+    pub fn layers_mut(&mut self) -> impl Iterator<Item=&mut FlagForeground>
+    {
+        self.layers.iter_mut().chain(self.canton.iter_mut().flat_map(|canton| canton.emblem.as_mut()))
+    }
+
+    /// visits every layer with the colors drawn before it, the canton's emblem last with only the canton's colors
+    fn layers_over(&mut self, mut visit: impl FnMut(&mut FlagForeground, &[Rgb<u8>]))
+    {
+        let mut under = self.background.lines.clone();
+        self.layers.iter_mut().for_each(|layer|
+        {
+            visit(layer, &under);
+
+            under.push(layer.edge_color());
+        });
+
+        if let Some(canton) = &mut self.canton
+        {
+            if let Some(emblem) = &mut canton.emblem
+            {
+                visit(emblem, &canton.background.lines);
+            }
+        }
+    }
+
+    /// outlines layers again after what's under them changed, dropping outlines that arent needed anymore
+    fn outline_layers(&mut self, config: &RandomConfig)
+    {
+        self.layers_over(|layer, under|
+        {
+            layer.outline = None;
+            *layer = layer.clone().outline_clashing(under, config.outline_contrast);
+        });
+    }
+
+    pub fn render(&self, width: u32, height: u32) -> RgbImage
+    {
+        create_flag(&self.background, &self.layers, self.canton.as_ref(), width, height)
+    }
+
+    pub fn render_svg(&self, width: u32, height: u32) -> String
+    {
+        create_flag_svg(&self.background, &self.layers, self.canton.as_ref(), width, height)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SpecError>
+    {
+        Self::load_with(path, &ShapeRegistry::default())
+    }
+
+    /// loads a spec which can use any shape from `shapes`
+    pub fn load_with(path: impl AsRef<Path>, shapes: &ShapeRegistry) -> Result<Self, SpecError>
+    {
+        Self::from_value_with(&Value::parse(&fs::read_to_string(path)?)?, shapes)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SpecError>
+    {
+        write_value(path, &self.to_value())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FlagBuilder
+{
+    spec: FlagSpec
+}
+
+impl FlagBuilder
+{
+    pub fn new(background: FlagBackground) -> Self
+    {
+        Self{spec: FlagSpec::new(background)}
+    }
+
+    /// adds a layer on top of the previous ones
+    pub fn layer(mut self, layer: FlagForeground) -> Self
+    {
+        self.spec.layers.push(layer);
+
+        self
+    }
+
+    pub fn canton(mut self, canton: Canton) -> Self
+    {
+        self.spec.canton = Some(canton);
+
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self
+    {
+        self.spec.seed = Some(seed);
+
+        self
+    }
+
+    pub fn build(self) -> FlagSpec
+    {
+        self.spec
+    }
+
+    pub fn render(&self, width: u32, height: u32) -> RgbImage
+    {
+        self.spec.render(width, height)
+    }
+}
+
+impl SpecValue for FlagSpec
+{
+    fn to_value(&self) -> Value
+    {
+        let mut fields = vec![("version", SPEC_VERSION.into())];
+
+        if let Some(seed) = self.seed
+        {
+            fields.push(("seed", seed.into()));
+        }
+
+        fields.push(("background", self.background.to_value()));
+        fields.push(("layers", Value::Array(self.layers.iter().map(SpecValue::to_value).collect())));
+        fields.push(("canton", self.canton.as_ref().map(SpecValue::to_value).unwrap_or(Value::Null)));
+
+        Value::object(fields)
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        Self::from_value_with(value, &ShapeRegistry::default())
+    }
+}
+
+impl FlagSpec
+{
+    pub fn from_value_with(value: &Value, shapes: &ShapeRegistry) -> Result<Self, SpecError>
+    {
+        let version = field(value, "version")?;
+        let version = version.as_u32().ok_or_else(|| invalid("version", version))?;
+
+        if version > SPEC_VERSION
+        {
+            return Err(SpecError::UnsupportedVersion(version));
+        }
+
+        let seed = value.get("seed")
+            .map(|x| x.as_u64().ok_or_else(|| invalid("seed", x)))
+            .transpose()?;
+
+        let layers = if version < 2
+        {
+            // version 1 had a single optional foreground
+            match value.get("foreground")
+            {
+                None | Some(Value::Null) => Vec::new(),
+                Some(x) => vec![FlagForeground::from_value_with(x, shapes)?]
+            }
+        } else
+        {
+            let layers = field(value, "layers")?;
+
+            layers.as_array().ok_or_else(|| invalid("layers", layers))?
+                .iter()
+                .map(|x| FlagForeground::from_value_with(x, shapes))
+                .collect::<Result<_, _>>()?
+        };
+
+        let canton = match value.get("canton")
+        {
+            None | Some(Value::Null) => None,
+            Some(x) => Some(Canton::from_value_with(x, shapes)?)
+        };
+
+        Ok(Self{
+            seed,
+            background: FlagBackground::from_value(field(value, "background")?)?,
+            layers,
+            canton
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::NordicCross;
+
+
+    #[test]
+    fn save_load_round_trip()
+    {
+        let path = std::env::temp_dir().join(format!("flaggen_round_trip_{}.json", std::process::id()));
+
+        (0..64).for_each(|seed|
+        {
+            let spec = FlagSpec::seeded(seed);
+
+            spec.save(&path).unwrap();
+            let loaded = FlagSpec::load(&path).unwrap();
+
+            assert_eq!(loaded.seed, Some(seed));
+            assert_eq!(loaded.render(90, 60), spec.render(90, 60), "seed {seed}");
+            assert_eq!(loaded.render_svg(90, 60), spec.render_svg(90, 60), "seed {seed}");
+        });
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_bad_colors()
+    {
+        ["#aééa", "#+f+f+f", "#12345", "123456"].into_iter().for_each(|text|
+        {
+            assert!(Rgb::<u8>::from_value(&text.into()).is_err(), "{text}");
+        });
+
+        assert_eq!(Rgb::<u8>::from_value(&"#00ff7f".into()).unwrap(), Rgb([0, 255, 127]));
+    }
+
+    #[test]
+    fn rejects_bad_layers()
+    {
+        let layer = |scale: &str, position: &str|
+        {
+            let text = format!(
+                r##"{{"color": "#ffffff", "shape": {{"type": "circle"}}, "scale": {scale}, "position": {position}}}"##
+            );
+
+            FlagForeground::from_value(&Value::parse(&text).unwrap())
+        };
+
+        assert!(layer("0.5", "[0.1, 0]").is_ok());
+        assert!(layer("-1", "[0, 0]").is_err());
+        assert!(layer("0", "[0, 0]").is_err());
+        assert!(layer("1", "[1e999, 0]").is_err());
+    }
+
+    #[test]
+    fn refuses_non_finite()
+    {
+        let path = std::env::temp_dir().join(format!("flaggen_non_finite_{}.json", std::process::id()));
+
+        let spec = FlagSpec::builder(FlagBackground::solid(Rgb([0, 0, 0])))
+            .layer(FlagForeground::new(Rgb([255, 255, 255]), FlagForegroundShape::Ring(f32::NAN)))
+            .build();
+
+        assert!(matches!(spec.save(&path), Err(SpecError::Invalid{..})));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn adjust_stays_in_range()
+    {
+        let shapes = RandomConfig::default().shapes;
+
+        let mut spec = FlagSpec::builder(FlagBackground::solid(Rgb([0, 0, 0])))
+            .layer(FlagForeground::new(Rgb([255, 255, 255]), FlagForegroundShape::Ring(0.05)))
+            .build();
+
+        while spec.adjust(&shapes, 0, "width", -1) {}
+
+        let width = spec.all_layers().next().unwrap().parameter("width").unwrap();
+        assert!((0.01..0.05).contains(&width), "width {width}");
+    }
+
+    #[test]
+    fn adjust_stops_at_whole_limits()
+    {
+        let shapes = RandomConfig::default().shapes;
+
+        let cross = NordicCross{hoist: 0.0, ..NordicCross::DENMARK};
+        let mut spec = FlagSpec::builder(FlagBackground::solid(Rgb([0, 0, 0])))
+            .layer(FlagForeground::new(Rgb([255, 255, 255]), FlagForegroundShape::NordicCross(cross)))
+            .build();
+
+        assert!(!spec.adjust(&shapes, 0, "hoist", -1));
+        assert!(spec.adjust(&shapes, 0, "hoist", 1));
+    }
+}
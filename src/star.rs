@@ -0,0 +1,245 @@
+use std::{
+    ops::RangeInclusive,
+    f32::consts::{PI, TAU}
+};
+
+use nalgebra::Vector2;
+
+use fastrand::Rng;
+
+use crate::{
+    json::Value,
+    spec::{self, SpecValue, SpecError}
+};
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Star
+{
+    pub points: u32,
+    /// inner radius as a fraction of the outer radius
+    pub ratio: f32,
+    /// in radians, at 0 one of the points faces straight up
+    pub rotation: f32,
+    /// offset of the center as a fraction of the flag's size
+    pub position: Vector2<f32>,
+    /// outer radius as a fraction of the flag's shorter side
+    pub size: f32
+}
+
+impl Star
+{
+    pub const POINTS: RangeInclusive<u32> = 2..=64;
+
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        let points = if rng.usize(0..4) == 0 { rng.u32(4..=8) } else { 5 };
+
+        Self{
+            points,
+            ratio: rng.f32() * 0.15 + 0.35,
+            rotation: 0.0,
+            position: Vector2::zeros(),
+            size: rng.f32() * 0.15 + 0.2
+        }
+    }
+
+    /// signed distance in pixels to a star centered at `center` with an outer radius of `radius` pixels
+    pub fn distance_at(&self, pos: Vector2<f32>, center: Vector2<f32>, radius: f32) -> f32
+    {
+        let offset = pos - center;
+
+        let sector = PI / self.points as f32;
+
+        // fold the position into the half sector between a point and the next inner corner
+        let angle = offset.y.atan2(offset.x) + PI / 2.0 - self.rotation;
+        let angle = angle.rem_euclid(sector * 2.0);
+        let angle = if angle > sector { sector * 2.0 - angle } else { angle };
+
+        let folded = Vector2::new(angle.cos(), angle.sin()) * offset.magnitude();
+
+        let outer = Vector2::new(radius, 0.0);
+        let inner = Vector2::new(sector.cos(), sector.sin()) * (radius * self.ratio);
+
+        let edge = inner - outer;
+        let local = folded - outer;
+
+        let along = (local.dot(&edge) / edge.dot(&edge)).clamp(0.0, 1.0);
+        let distance = (local - edge * along).magnitude();
+
+        let outside = edge.x * local.y - edge.y * local.x < 0.0;
+
+        if outside { distance } else { -distance }
+    }
+
+    pub fn vertices(&self, center: Vector2<f32>, radius: f32) -> Vec<Vector2<f32>>
+    {
+        let sector = PI / self.points as f32;
+
+        (0..self.points * 2).map(|index|
+        {
+            let angle = self.rotation - PI / 2.0 + sector * index as f32;
+            let radius = if index % 2 == 0 { radius } else { radius * self.ratio };
+
+            center + Vector2::new(angle.cos(), angle.sin()) * radius
+        }).collect()
+    }
+
+    pub fn description(&self) -> String
+    {
+        format!("{} pointed star", self.points)
+    }
+
+    pub fn parameters(&self) -> Vec<(&'static str, Value)>
+    {
+        vec![
+            ("points", self.points.into()),
+            ("ratio", self.ratio.into()),
+            ("rotation", self.rotation.into()),
+            ("position", self.position.to_value()),
+            ("size", self.size.into())
+        ]
+    }
+
+    pub fn from_parameters(value: &Value) -> Result<Self, SpecError>
+    {
+        Ok(Self{
+            points: spec::u32_in(value, "points", Self::POINTS)?,
+            ratio: spec::f32_in(value, "ratio", 0.05..=1.0)?,
+            rotation: spec::f32_field(value, "rotation")?,
+            position: Vector2::from_value(spec::field(value, "position")?)?,
+            size: spec::f32_in(value, "size", 0.01..=2.0)?
+        })
+    }
+}
+
+/// how a group of stars is laid out around the star's position
+#[derive(Debug, Clone, PartialEq)]
+pub enum StarArrangement
+{
+    Row{count: u32, spacing: f32},
+    Ring{count: u32, radius: f32},
+    /// offsets of each star as fractions of the flag's shorter side
+    Constellation(Vec<Vector2<f32>>)
+}
+
+impl StarArrangement
+{
+    pub fn random(rng: &mut Rng) -> Self
+    {
+        match rng.usize(0..3)
+        {
+            0 => Self::Row{count: rng.u32(2..=6), spacing: rng.f32() * 0.1 + 0.15},
+            1 => Self::Ring{count: rng.u32(5..=12), radius: rng.f32() * 0.1 + 0.25},
+            2 =>
+            {
+                let offsets = (0..rng.usize(3..=6)).map(|_|
+                {
+                    Vector2::new(rng.f32() - 0.5, rng.f32() - 0.5) * 0.7
+                }).collect();
+
+                Self::Constellation(offsets)
+            },
+            _ => unreachable!()
+        }
+    }
+
+    /// size of each star that fits the arrangement
+    pub fn random_star_size(&self, rng: &mut Rng) -> f32
+    {
+        let limit = match self
+        {
+            Self::Row{spacing, ..} => spacing / 2.0,
+            Self::Ring{count, radius} => radius * PI / *count as f32,
+            Self::Constellation(_) => 0.1
+        };
+
+        limit * (rng.f32() * 0.3 + 0.5)
+    }
+
+    /// offsets of each star as fractions of the flag's shorter side
+    pub fn offsets(&self) -> Vec<Vector2<f32>>
+    {
+        match self
+        {
+            Self::Row{count, spacing} =>
+            {
+                let start = -(*count as f32 - 1.0) / 2.0;
+
+                (0..*count).map(|index| Vector2::new((start + index as f32) * spacing, 0.0)).collect()
+            },
+            Self::Ring{count, radius} =>
+            {
+                (0..*count).map(|index|
+                {
+                    let angle = TAU * index as f32 / *count as f32 - PI / 2.0;
+
+                    Vector2::new(angle.cos(), angle.sin()) * *radius
+                }).collect()
+            },
+            Self::Constellation(offsets) => offsets.clone()
+        }
+    }
+
+    pub fn description(&self) -> String
+    {
+        match self
+        {
+            Self::Row{count, ..} => format!("row of {count}"),
+            Self::Ring{count, ..} => format!("ring of {count}"),
+            Self::Constellation(offsets) => format!("constellation of {}", offsets.len())
+        }
+    }
+}
+
+impl SpecValue for StarArrangement
+{
+    fn to_value(&self) -> Value
+    {
+        match self
+        {
+            Self::Row{count, spacing} =>
+            {
+                Value::object([("type", "row".into()), ("count", (*count).into()), ("spacing", (*spacing).into())])
+            },
+            Self::Ring{count, radius} =>
+            {
+                Value::object([("type", "ring".into()), ("count", (*count).into()), ("radius", (*radius).into())])
+            },
+            Self::Constellation(offsets) =>
+            {
+                Value::object([
+                    ("type", "constellation".into()),
+                    ("offsets", Value::Array(offsets.iter().map(SpecValue::to_value).collect()))
+                ])
+            }
+        }
+    }
+
+    fn from_value(value: &Value) -> Result<Self, SpecError>
+    {
+        Ok(match spec::str_field(value, "type")?
+        {
+            "row" => Self::Row{
+                count: spec::u32_in(value, "count", 1..=256)?,
+                spacing: spec::f32_in(value, "spacing", 0.01..=1.0)?
+            },
+            "ring" => Self::Ring{
+                count: spec::u32_in(value, "count", 1..=256)?,
+                radius: spec::f32_in(value, "radius", 0.01..=1.0)?
+            },
+            "constellation" =>
+            {
+                let offsets = spec::field(value, "offsets")?;
+
+                let offsets = offsets.as_array().ok_or_else(|| spec::invalid("offsets", offsets))?
+                    .iter()
+                    .map(Vector2::from_value)
+                    .collect::<Result<_, _>>()?;
+
+                Self::Constellation(offsets)
+            },
+            _ => return Err(spec::invalid("type", spec::field(value, "type")?))
+        })
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::Write;
+
+use nalgebra::Vector2;
+
+use image::Rgb;
+
+use crate::{
+    hex_color,
+    FlagShape,
+    Canton,
+    FlagBackground,
+    FlagForeground
+};
+
+
+pub fn polygon(output: &mut String, color: &str, points: &[Vector2<f32>])
+{
+    let points = points.iter().map(|point| format!("{},{}", point.x, point.y)).collect::<Vec<_>>();
+
+    writeln!(output, r#"<polygon fill="{color}" points="{}"/>"#, points.join(" ")).unwrap();
+}
+
+pub fn path(output: &mut String, color: &str, data: &str)
+{
+    writeln!(output, r#"<path fill="{color}" d="{data}"/>"#).unwrap();
+}
+
+pub fn rect(output: &mut String, color: &str, position: Vector2<f32>, size: Vector2<f32>)
+{
+    writeln!(
+        output,
+        r#"<rect fill="{color}" x="{}" y="{}" width="{}" height="{}"/>"#,
+        position.x, position.y, size.x, size.y
+    ).unwrap();
+}
+
+pub fn circle(output: &mut String, color: &str, center: Vector2<f32>, radius: f32)
+{
+    writeln!(
+        output,
+        r#"<circle fill="{color}" cx="{}" cy="{}" r="{}"/>"#,
+        center.x, center.y, radius
+    ).unwrap();
+}
+
+/// `radius` is measured to the middle of the ring
+pub fn ring(output: &mut String, color: &str, center: Vector2<f32>, radius: f32, width: f32)
+{
+    // filled instead of stroked so outlines can stroke it like any other shape
+    let circle = |radius: f32|
+    {
+        format!(
+            "M {} {} A {radius} {radius} 0 1 0 {} {} A {radius} {radius} 0 1 0 {} {} Z",
+            center.x - radius, center.y,
+            center.x + radius, center.y,
+            center.x - radius, center.y
+        )
+    };
+
+    writeln!(
+        output,
+        r#"<path fill="{color}" fill-rule="evenodd" d="{} {}"/>"#,
+        circle(radius + width / 2.0),
+        circle(radius - width / 2.0)
+    ).unwrap();
+}
+
+fn background_svg(output: &mut String, background: &FlagBackground, size: Vector2<f32>)
+{
+    let amount = background.lines.len();
+
+    let Some((horizontal, ends)) = background.stripes() else
+    {
+        rect(output, &hex_color(background.color(0)), Vector2::zeros(), size);
+
+        background.division.regions(amount, size).into_iter().for_each(|region|
+        {
+            polygon(output, &hex_color(background.color(region.color)), &region.points);
+        });
+
+        return;
+    };
+
+    let mut start = 0.0;
+    ends.into_iter().enumerate().for_each(|(index, end)|
+    {
+        let (position, line_size) = if horizontal
+        {
+            (Vector2::new(size.x * start, 0.0), Vector2::new(size.x * (end - start), size.y))
+        } else
+        {
+            (Vector2::new(0.0, size.y * start), Vector2::new(size.x, size.y * (end - start)))
+        };
+
+        rect(output, &hex_color(background.color(index)), position, line_size);
+
+        start = end;
+    });
+}
+
+/// the shape's own elements stroked to grow them by `grow` pixels
+fn border_svg(output: &mut String, shape: &impl FlagShape, color: Rgb<u8>, grow: f32, size: Vector2<f32>)
+{
+    let color = hex_color(color);
+    let width = grow * 2.0;
+
+    writeln!(output, r#"<g stroke="{color}" stroke-width="{width}" stroke-linejoin="round">"#).unwrap();
+
+    shape.svg(output, &color, size);
+
+    output.push_str("</g>\n");
+}
+
+fn shape_svg(output: &mut String, foreground: &FlagForeground, size: Vector2<f32>)
+{
+    let lower_size = size.x.min(size.y);
+
+    let fimbriation = foreground.shape.fimbriation();
+    let fimbriation_width = fimbriation.map(|x| x.width).unwrap_or(0.0);
+
+    if let Some(outline) = foreground.outline
+    {
+        let grow = (fimbriation_width + outline.width) * lower_size;
+
+        border_svg(output, &foreground.shape, outline.color, grow, size);
+    }
+
+    if let Some(fimbriation) = fimbriation
+    {
+        border_svg(output, &foreground.shape, fimbriation.color, fimbriation_width * lower_size, size);
+    }
+
+    foreground.shape.svg(output, &hex_color(foreground.color), size);
+}
+
+fn foreground_svg(output: &mut String, foreground: &FlagForeground, size: Vector2<f32>)
+{
+    if !foreground.is_transformed()
+    {
+        shape_svg(output, foreground, size);
+
+        return;
+    }
+
+    // a nested svg clips the shape to its scaled copy of the flag
+    let scaled = size * foreground.scale;
+    let position = (size - scaled) / 2.0 + foreground.position.component_mul(&size);
+
+    writeln!(
+        output,
+        r#"<svg x="{}" y="{}" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        position.x, position.y, scaled.x, scaled.y, size.x, size.y
+    ).unwrap();
+
+    shape_svg(output, foreground, size);
+
+    output.push_str("</svg>\n");
+}
+
+fn field_svg(
+    output: &mut String,
+    background: &FlagBackground,
+    layers: &[FlagForeground],
+    size: Vector2<f32>
+)
+{
+    background_svg(output, background, size);
+
+    layers.iter().for_each(|layer| foreground_svg(output, layer, size));
+}
+
+pub fn create_flag_svg(
+    background: &FlagBackground,
+    layers: &[FlagForeground],
+    canton: Option<&Canton>,
+    width: u32,
+    height: u32
+) -> String
+{
+    let size = Vector2::new(width as f32, height as f32);
+
+    let mut output = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+
+    output.push('\n');
+
+    field_svg(&mut output, background, layers, size);
+
+    if let Some(canton) = canton
+    {
+        let (canton_width, canton_height) = canton.pixel_size(width, height);
+
+        writeln!(
+            output,
+            r#"<svg x="0" y="0" width="{canton_width}" height="{canton_height}" viewBox="0 0 {canton_width} {canton_height}">"#
+        ).unwrap();
+
+        let canton_size = Vector2::new(canton_width as f32, canton_height as f32);
+        field_svg(&mut output, &canton.background, canton.emblem.as_slice(), canton_size);
+
+        output.push_str("</svg>\n");
+    }
+
+    output.push_str("</svg>\n");
+
+    output
+}
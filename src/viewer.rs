@@ -0,0 +1,544 @@
+use std::{
+    collections::VecDeque,
+    path::PathBuf
+};
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    render::{WindowCanvas, TextureAccess, Texture, TextureCreator, BlendMode},
+    video::WindowContext,
+    rect::Rect
+};
+
+use nalgebra::Vector2;
+
+use image::RgbImage;
+
+use fastrand::Rng;
+
+use flaggen::{FlagSpec, RandomConfig, vision::Deficiency};
+
+use crate::cli::{ViewerOptions, CliError};
+
+
+/// 3 by 5 pixel glyphs, one row per 3 bits from the top
+const GLYPHS: [(char, [u8; 5]); 11] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b010, 0b010, 0b010]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100])
+];
+
+const GLYPH_SCALE: u32 = 3;
+
+/// previously shown specs, oldest first
+pub struct History
+{
+    specs: VecDeque<FlagSpec>,
+    current: usize,
+    length: usize
+}
+
+impl History
+{
+    /// starts at the first of `specs`, remembers all of them even if theres more than `length`
+    pub fn new(specs: Vec<FlagSpec>, length: usize) -> Self
+    {
+        assert!(!specs.is_empty(), "history must start with at least one spec");
+
+        let length = length.max(specs.len());
+
+        Self{specs: specs.into(), current: 0, length}
+    }
+
+    pub fn current(&self) -> &FlagSpec
+    {
+        &self.specs[self.current]
+    }
+
+    /// adds a spec after every other one and moves to it, forgetting the oldest if theres too many
+    pub fn push(&mut self, spec: FlagSpec)
+    {
+        self.specs.push_back(spec);
+
+        while self.specs.len() > self.length
+        {
+            self.specs.pop_front();
+        }
+
+        self.current = self.specs.len() - 1;
+    }
+
+    /// moves to the previous spec, `false` if already at the oldest
+    pub fn back(&mut self) -> bool
+    {
+        let moved = self.current > 0;
+        if moved
+        {
+            self.current -= 1;
+        }
+
+        moved
+    }
+
+    /// moves to the next spec, `false` if already at the newest
+    pub fn forward(&mut self) -> bool
+    {
+        let moved = self.current + 1 < self.specs.len();
+        if moved
+        {
+            self.current += 1;
+        }
+
+        moved
+    }
+
+    /// position of the current spec counting from 1, and the amount of specs
+    pub fn counter(&self) -> (usize, usize)
+    {
+        (self.current + 1, self.specs.len())
+    }
+
+    /// replaces the current spec
+    pub fn replace(&mut self, spec: FlagSpec)
+    {
+        self.specs[self.current] = spec;
+    }
+}
+
+/// state of the edit mode
+struct Editor
+{
+    /// index into `Viewer::parameters`, wrapping around
+    selected: usize,
+    /// the spec being edited is already a copy, so the original stays in the history
+    copied: bool
+}
+
+pub struct Viewer<'a>
+{
+    canvas: WindowCanvas,
+    creator: &'a TextureCreator<WindowContext>,
+    texture: Option<Texture<'a>>,
+    options: ViewerOptions,
+    history: History,
+    flag: RgbImage,
+    vision: Option<Deficiency>,
+    editor: Option<Editor>
+}
+
+impl<'a> Viewer<'a>
+{
+    pub fn new(
+        canvas: WindowCanvas,
+        creator: &'a TextureCreator<WindowContext>,
+        options: ViewerOptions,
+        specs: Vec<FlagSpec>
+    ) -> Self
+    {
+        let history = History::new(specs, options.history);
+
+        let mut this = Self{
+            canvas,
+            creator,
+            texture: None,
+            options,
+            history,
+            flag: RgbImage::new(1, 1),
+            vision: None,
+            editor: None
+        };
+
+        this.show_current();
+
+        this
+    }
+
+    pub fn options(&self) -> &ViewerOptions
+    {
+        &self.options
+    }
+
+    /// shows a new spec after everything in the history
+    pub fn show(&mut self, spec: FlagSpec)
+    {
+        self.history.push(spec);
+
+        self.show_other();
+    }
+
+    /// shows a changed copy of the current spec, keeping the original in the history
+    pub fn reroll(&mut self, reroll: impl FnOnce(&mut FlagSpec, &mut Rng, &RandomConfig))
+    {
+        let mut spec = self.history.current().clone();
+        reroll(&mut spec, &mut Rng::new(), &self.options.shared.config);
+
+        self.show(spec);
+    }
+
+    pub fn back(&mut self)
+    {
+        if self.history.back()
+        {
+            self.show_other();
+        }
+    }
+
+    pub fn forward(&mut self)
+    {
+        if self.history.forward()
+        {
+            self.show_other();
+        }
+    }
+
+    /// shows the current spec after moving to a different one
+    fn show_other(&mut self)
+    {
+        if let Some(editor) = &mut self.editor
+        {
+            editor.copied = false;
+        }
+
+        self.show_current();
+    }
+
+    pub fn is_editing(&self) -> bool
+    {
+        self.editor.is_some()
+    }
+
+    pub fn toggle_editing(&mut self)
+    {
+        self.editor = if self.editor.is_some() { None } else { Some(Editor{selected: 0, copied: false}) };
+
+        self.update_title();
+    }
+
+    /// every parameter of every layer as the layer's index and the parameter's name
+    fn parameters(&self) -> Vec<(usize, &'static str)>
+    {
+        self.history.current().all_layers().enumerate().flat_map(|(index, layer)|
+        {
+            layer.parameter_names().into_iter().map(move |name| (index, name))
+        }).collect()
+    }
+
+    fn selected(&self) -> Option<(usize, &'static str)>
+    {
+        let parameters = self.parameters();
+
+        self.editor.as_ref().filter(|_| !parameters.is_empty()).map(|editor|
+        {
+            parameters[editor.selected % parameters.len()]
+        })
+    }
+
+    /// moves the selection `offset` parameters further
+    pub fn select(&mut self, offset: isize)
+    {
+        let amount = self.parameters().len();
+
+        if let Some(editor) = self.editor.as_mut().filter(|_| amount > 0)
+        {
+            editor.selected = (editor.selected % amount).saturating_add_signed(offset + amount as isize) % amount;
+        }
+
+        self.update_title();
+    }
+
+    /// moves the selected parameter by `steps` steps
+    pub fn adjust(&mut self, steps: i32)
+    {
+        if let Some((layer, name)) = self.selected()
+        {
+            let shapes = self.options.shared.config.shapes.clone();
+
+            self.edit(|spec| spec.adjust(&shapes, layer, name, steps));
+        }
+    }
+
+    /// gives the part of the background under a point in the window a new color
+    pub fn recolor_at(&mut self, x: i32, y: i32)
+    {
+        let target = self.letterbox();
+        if !target.contains_point((x, y))
+        {
+            return;
+        }
+
+        let pos = Vector2::new((x - target.x()) as f32 + 0.5, (y - target.y()) as f32 + 0.5);
+        let size = Vector2::new(target.width() as f32, target.height() as f32);
+
+        let config = self.options.shared.config.clone();
+        self.edit(|spec|
+        {
+            spec.recolor_at(&mut Rng::new(), &config, pos, size);
+
+            true
+        });
+    }
+
+    /// changes the current spec in place, after the first change it's a copy of the original
+    fn edit(&mut self, edit: impl FnOnce(&mut FlagSpec) -> bool)
+    {
+        let Some(editor) = &mut self.editor else
+        {
+            return;
+        };
+
+        let mut spec = self.history.current().clone();
+        if !edit(&mut spec)
+        {
+            return;
+        }
+
+        if editor.copied
+        {
+            self.history.replace(spec);
+        } else
+        {
+            editor.copied = true;
+            self.history.push(spec);
+        }
+
+        self.show_current();
+    }
+
+    pub fn save(&self) -> Result<PathBuf, CliError>
+    {
+        self.options.save(self.history.current())
+    }
+
+    pub fn add_favorite(&self) -> Result<PathBuf, CliError>
+    {
+        self.options.add_favorite(self.history.current())
+    }
+
+    /// renders the current spec again to fit the window's new size
+    pub fn resized(&mut self)
+    {
+        self.show_current();
+    }
+
+    /// renders the current spec at the size it takes up in the window
+    fn show_current(&mut self)
+    {
+        self.update_title();
+
+        let target = self.letterbox();
+        self.flag = self.history.current().render(target.width(), target.height());
+
+        self.update_texture();
+    }
+
+    pub fn cycle_vision(&mut self)
+    {
+        self.vision = Deficiency::cycle(self.vision);
+
+        self.update_title();
+        self.update_texture();
+    }
+
+    fn update_title(&mut self)
+    {
+        let mut title = match self.history.current().seed
+        {
+            Some(seed) => format!("flag generator! (seed {seed})"),
+            None => "flag generator!".to_owned()
+        };
+
+        if let Some(vision) = self.vision
+        {
+            title += &format!(" [{vision}]");
+        }
+
+        if self.editor.is_some()
+        {
+            title += &match self.selected()
+            {
+                Some((layer, name)) =>
+                {
+                    let value = self.history.current().all_layers().nth(layer)
+                        .and_then(|x| x.parameter(name))
+                        .unwrap_or_default();
+
+                    format!(" [editing layer {} {name} = {value:.3}]", layer + 1)
+                },
+                None => " [editing]".to_owned()
+            };
+        }
+
+        self.canvas.window_mut().set_title(&title).unwrap();
+    }
+
+    fn update_texture(&mut self)
+    {
+        let simulated = self.vision.map(|vision| vision.simulate_image(&self.flag));
+        let shown = simulated.as_ref().unwrap_or(&self.flag);
+
+        let mut texture = self.creator.create_texture(
+            PixelFormatEnum::RGB24,
+            TextureAccess::Static,
+            shown.width(),
+            shown.height()
+        ).unwrap();
+
+        texture.update(None, shown, shown.width() as usize * 3).unwrap();
+
+        self.texture = Some(texture);
+
+        self.present();
+    }
+
+    /// the biggest rectangle with the flag's aspect ratio that fits in the window, centered
+    fn letterbox(&self) -> Rect
+    {
+        let (window_width, window_height) = self.canvas.window().size();
+        let (flag_width, flag_height) = self.options.flag_size();
+
+        let scale = (window_width as f32 / flag_width as f32).min(window_height as f32 / flag_height as f32);
+
+        let width = ((flag_width as f32 * scale).round() as u32).max(1);
+        let height = ((flag_height as f32 * scale).round() as u32).max(1);
+
+        Rect::new(
+            (window_width.saturating_sub(width) / 2) as i32,
+            (window_height.saturating_sub(height) / 2) as i32,
+            width,
+            height
+        )
+    }
+
+    pub fn present(&mut self)
+    {
+        self.canvas.set_draw_color(Color::RGB(0, 0, 0));
+        self.canvas.clear();
+
+        let target = self.letterbox();
+        if let Some(texture) = &self.texture
+        {
+            self.canvas.copy(texture, None, target).unwrap();
+        }
+
+        let (current, total) = self.history.counter();
+        self.draw_overlay(&format!("{current}/{total}"));
+
+        self.canvas.present();
+    }
+
+    /// draws `text` in the top left corner over a dark box, only knows digits and slashes
+    fn draw_overlay(&mut self, text: &str)
+    {
+        let padding = GLYPH_SCALE as i32 * 2;
+        let advance = GLYPH_SCALE as i32 * 4;
+
+        let width = text.chars().count() as i32 * advance - GLYPH_SCALE as i32 + padding * 2;
+        let height = GLYPH_SCALE as i32 * 5 + padding * 2;
+
+        self.canvas.set_blend_mode(BlendMode::Blend);
+
+        self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+        self.canvas.fill_rect(Rect::new(0, 0, width as u32, height as u32)).unwrap();
+
+        self.canvas.set_draw_color(Color::RGB(255, 255, 255));
+
+        let pixels: Vec<Rect> = text.chars().enumerate().filter_map(|(index, c)|
+        {
+            GLYPHS.iter().find(|(glyph, _)| *glyph == c).map(|(_, rows)| (index as i32, rows))
+        }).flat_map(|(index, rows)|
+        {
+            rows.iter().enumerate().flat_map(move |(y, row)|
+            {
+                (0..3).filter(move |x| (row >> (2 - x)) & 1 == 1).map(move |x|
+                {
+                    Rect::new(
+                        padding + index * advance + x * GLYPH_SCALE as i32,
+                        padding + y as i32 * GLYPH_SCALE as i32,
+                        GLYPH_SCALE,
+                        GLYPH_SCALE
+                    )
+                })
+            })
+        }).collect();
+
+        self.canvas.fill_rects(&pixels).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    fn history(seeds: impl IntoIterator<Item=u64>, length: usize) -> History
+    {
+        History::new(seeds.into_iter().map(FlagSpec::seeded).collect(), length)
+    }
+
+    fn seeds(history: &History) -> Vec<Option<u64>>
+    {
+        history.specs.iter().map(|spec| spec.seed).collect()
+    }
+
+    #[test]
+    fn trims_oldest()
+    {
+        let mut history = history([0], 3);
+
+        (1..5).for_each(|seed| history.push(FlagSpec::seeded(seed)));
+
+        assert_eq!(seeds(&history), [Some(2), Some(3), Some(4)]);
+        assert_eq!(history.current().seed, Some(4));
+        assert_eq!(history.counter(), (3, 3));
+    }
+
+    #[test]
+    fn keeps_every_starting_spec()
+    {
+        let history = history(0..5, 2);
+
+        assert_eq!(history.counter(), (1, 5));
+        assert_eq!(history.current().seed, Some(0));
+    }
+
+    #[test]
+    fn stops_at_ends()
+    {
+        let mut history = history(0..3, 10);
+
+        assert!(!history.back());
+        assert_eq!(history.current().seed, Some(0));
+
+        assert!(history.forward());
+        assert!(history.forward());
+        assert!(!history.forward());
+        assert_eq!(history.current().seed, Some(2));
+
+        assert!(history.back());
+        assert_eq!(history.counter(), (2, 3));
+    }
+
+    #[test]
+    fn pushes_after_newest()
+    {
+        let mut history = history(0..3, 10);
+
+        assert!(history.forward());
+        history.push(FlagSpec::seeded(7));
+
+        assert_eq!(seeds(&history), [Some(0), Some(1), Some(2), Some(7)]);
+        assert_eq!(history.counter(), (4, 4));
+
+        history.replace(FlagSpec::seeded(8));
+        assert_eq!(seeds(&history), [Some(0), Some(1), Some(2), Some(8)]);
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt::{self, Display};
+
+use nalgebra::{Matrix3, Vector3};
+
+use image::{RgbImage, Rgb};
+
+use crate::{
+    luminance,
+    hex_color,
+    color::{to_linear, from_linear}
+};
+
+
+/// a kind of color blindness
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deficiency
+{
+    /// no red cones
+    Protanopia,
+    /// no green cones
+    Deuteranopia,
+    /// no blue cones
+    Tritanopia,
+    /// no color at all
+    Achromatopsia
+}
+
+impl Display for Deficiency
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let name = match self
+        {
+            Self::Protanopia => "protanopia",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Tritanopia => "tritanopia",
+            Self::Achromatopsia => "achromatopsia"
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+impl Deficiency
+{
+    pub const ALL: [Self; 4] = [Self::Protanopia, Self::Deuteranopia, Self::Tritanopia, Self::Achromatopsia];
+
+    /// the next deficiency to show after `current`, wrapping around to `None`
+    pub fn cycle(current: Option<Self>) -> Option<Self>
+    {
+        match current
+        {
+            None => Some(Self::ALL[0]),
+            Some(current) =>
+            {
+                let index = Self::ALL.iter().position(|x| *x == current).unwrap();
+
+                Self::ALL.get(index + 1).copied()
+            }
+        }
+    }
+
+    /// in linear rgb, from machado et al. 2009 at full severity
+    fn matrix(&self) -> Option<Matrix3<f32>>
+    {
+        match self
+        {
+            Self::Protanopia => Some(Matrix3::new(
+                0.152286, 1.052583, -0.204868,
+                0.114503, 0.786281, 0.099216,
+                -0.003882, -0.048116, 1.051998
+            )),
+            Self::Deuteranopia => Some(Matrix3::new(
+                0.367322, 0.860646, -0.227968,
+                0.280085, 0.672501, 0.047413,
+                -0.011820, 0.042940, 0.968881
+            )),
+            Self::Tritanopia => Some(Matrix3::new(
+                1.255528, -0.076749, -0.178779,
+                -0.078411, 0.930809, 0.147602,
+                0.004733, 0.691367, 0.303900
+            )),
+            Self::Achromatopsia => None
+        }
+    }
+
+    /// how `color` looks to someone with this deficiency
+    pub fn simulate(&self, color: Rgb<u8>) -> Rgb<u8>
+    {
+        match self.matrix()
+        {
+            Some(matrix) =>
+            {
+                let linear = matrix * Vector3::from(color.0.map(to_linear));
+
+                Rgb([from_linear(linear.x), from_linear(linear.y), from_linear(linear.z)])
+            },
+            None => Rgb([from_linear(luminance(color)); 3])
+        }
+    }
+
+    pub fn simulate_image(&self, image: &RgbImage) -> RgbImage
+    {
+        let mut image = image.clone();
+        image.pixels_mut().for_each(|pixel| *pixel = self.simulate(*pixel));
+
+        image
+    }
+
+    /// replaces every `#rrggbb` color in an svg
+    pub fn simulate_svg(&self, svg: &str) -> String
+    {
+        let mut output = String::with_capacity(svg.len());
+
+        let mut rest = svg;
+        while let Some(start) = rest.find('#')
+        {
+            output.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let color = rest.get(1..7)
+                .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+
+            if let Some(color) = color
+            {
+                let [_, r, g, b] = color.to_be_bytes();
+
+                output.push_str(&hex_color(self.simulate(Rgb([r, g, b]))));
+                rest = &rest[7..];
+            } else
+            {
+                output.push('#');
+                rest = &rest[1..];
+            }
+        }
+
+        output.push_str(rest);
+
+        output
+    }
+}